@@ -17,9 +17,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
+use basset::access_control::RolesResponse;
 use basset::hub::{
-    AllHistoryResponse, Config, CurrentBatchResponse, ExecuteMsg, InstantiateMsg, Parameters,
-    QueryMsg, State, StateResponse, UnbondRequestsResponse, WithdrawableUnbondedResponse,
+    AllHistoryResponse, AprResponse, Config, CurrentBatchResponse, ExchangeRateHistoryResponse,
+    ExecuteMsg, InstantiateMsg, Parameters, PauseStateResponse, PendingChangesResponse, QueryMsg,
+    SlashingEventsResponse, State, StateResponse, UnbondClaimResponse, UnbondRequestsResponse,
+    WithdrawableUnbondedResponse,
 };
 
 fn main() {
@@ -39,4 +42,11 @@ fn main() {
     export_schema(&schema_for!(UnbondRequestsResponse), &out_dir);
     export_schema(&schema_for!(CurrentBatchResponse), &out_dir);
     export_schema(&schema_for!(AllHistoryResponse), &out_dir);
+    export_schema(&schema_for!(UnbondClaimResponse), &out_dir);
+    export_schema(&schema_for!(ExchangeRateHistoryResponse), &out_dir);
+    export_schema(&schema_for!(AprResponse), &out_dir);
+    export_schema(&schema_for!(SlashingEventsResponse), &out_dir);
+    export_schema(&schema_for!(PendingChangesResponse), &out_dir);
+    export_schema(&schema_for!(RolesResponse), &out_dir);
+    export_schema(&schema_for!(PauseStateResponse), &out_dir);
 }
@@ -12,10 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::contract::slashing;
+use crate::contract::{query_total_statom_issued, slashing};
 use crate::math::decimal_division;
-use crate::state::{CONFIG, CURRENT_BATCH, PARAMETERS, STATE};
-use basset::hub::{BondType, Parameters};
+use crate::state::{
+    increase_book_delegation, load_parameters, store_exchange_rate_snapshot, CONFIG, CURRENT_BATCH,
+    STATE,
+};
+use basset::hub::{BondType, Parameters, PausableOperation};
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, to_binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, QueryRequest, Response,
     StakingMsg, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
@@ -25,18 +29,62 @@ use lido_cosmos_validators_registry::common::calculate_delegations;
 use lido_cosmos_validators_registry::msg::QueryMsg as QueryValidators;
 use lido_cosmos_validators_registry::registry::ValidatorResponse;
 
+/// Delegate the amount between the validators from the registry
+pub(crate) fn delegate_to_validators(
+    deps: &mut DepsMut,
+    amount: Uint128,
+    denom: &str,
+) -> StdResult<Vec<CosmosMsg>> {
+    let config = CONFIG.load(deps.storage)?;
+    let validators_registry_contract = if let Some(v) = config.validators_registry_contract {
+        v
+    } else {
+        return Err(StdError::generic_err(
+            "Validators registry contract address is empty",
+        ));
+    };
+    let validators: Vec<ValidatorResponse> =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: validators_registry_contract.to_string(),
+            msg: to_binary(&QueryValidators::GetValidatorsForDelegation {})?,
+        }))?;
+
+    if validators.is_empty() {
+        return Err(StdError::generic_err("Validators registry is empty"));
+    }
+
+    let delegations = calculate_delegations(amount, validators.as_slice())?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for i in 0..delegations.len() {
+        if delegations[i].is_zero() {
+            continue;
+        }
+        increase_book_delegation(deps.storage, &validators[i].address, delegations[i])?;
+        messages.push(cosmwasm_std::CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: validators[i].address.clone(),
+            amount: Coin::new(delegations[i].u128(), denom),
+        }));
+    }
+    Ok(messages)
+}
+
 pub fn execute_bond(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     bond_type: BondType,
 ) -> Result<Response, StdError> {
-    let params: Parameters = PARAMETERS.load(deps.storage)?;
-    if params.paused.unwrap_or(false) {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    let operation = match bond_type {
+        BondType::StAtom => PausableOperation::Bond,
+        BondType::BondRewards => PausableOperation::Rewards,
+    };
+    if params.is_paused(operation) {
         return Err(StdError::generic_err("the contract is temporarily paused"));
     }
 
-    let coin_denom = params.underlying_coin_denom;
+    let coin_denom = params.underlying_coin_denom.clone();
     let config = CONFIG.load(deps.storage)?;
 
     let reward_dispatcher_addr = config.reward_dispatcher_contract.ok_or_else(|| {
@@ -67,8 +115,15 @@ pub fn execute_bond(
             StdError::generic_err(format!("No {} assets are provided to bond", coin_denom))
         })?;
 
+    if bond_type == BondType::StAtom && payment.amount < params.min_bond_amount {
+        return Err(StdError::generic_err(format!(
+            "Bond amount must be at least {}{}",
+            params.min_bond_amount, coin_denom
+        )));
+    }
+
     // check slashing
-    let state = slashing(&mut deps, env)?;
+    let (state, insurance_msgs) = slashing(&mut deps, env.clone())?;
 
     let sender = info.sender.clone();
 
@@ -79,53 +134,91 @@ pub fn execute_bond(
         BondType::StAtom => decimal_division(payment.amount, state.statom_exchange_rate),
         BondType::BondRewards => Uint128::zero(),
     };
+    if bond_type == BondType::StAtom && mint_amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Bond amount is too small to mint stAtom",
+        ));
+    }
 
     // total supply should be updated for exchange rate calculation.
     total_supply += mint_amount;
 
-    // exchange rate should be updated for future
-    STATE.update(deps.storage, |mut prev_state| -> StdResult<_> {
-        match bond_type {
-            BondType::BondRewards => {
-                prev_state.total_bond_statom_amount += payment.amount;
-                prev_state.update_statom_exchange_rate(total_supply, requested_with_fee);
-                Ok(prev_state)
-            }
-            BondType::StAtom => {
-                prev_state.total_bond_statom_amount += payment.amount;
-                Ok(prev_state)
-            }
+    // until the supply reaches the minimum only the owner can bond and the minted tokens are
+    // kept by the hub as dead shares, so the rate can't be inflated by a donation to a tiny supply
+    let dead_shares = if bond_type == BondType::StAtom && !params.min_total_supply.is_zero() {
+        let missing_supply = params
+            .min_total_supply
+            .saturating_sub(query_total_statom_issued(deps.as_ref())?);
+        if !missing_supply.is_zero() && sender != config.creator {
+            return Err(StdError::generic_err(format!(
+                "Bonding is disabled until the owner has bonded the minimum supply of {} stAtom",
+                params.min_total_supply
+            )));
         }
-    })?;
-
-    let validators_registry_contract = if let Some(v) = config.validators_registry_contract {
-        v
+        missing_supply.min(mint_amount)
     } else {
-        return Err(StdError::generic_err(
-            "Validators registry contract address is empty",
-        ));
+        Uint128::zero()
     };
-    let validators: Vec<ValidatorResponse> =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: validators_registry_contract.to_string(),
-            msg: to_binary(&QueryValidators::GetValidatorsForDelegation {})?,
-        }))?;
 
-    if validators.is_empty() {
-        return Err(StdError::generic_err("Validators registry is empty"));
+    // the rewards raising the rate above the bound are deferred instead of bonded
+    let mut bonded_amount = payment.amount;
+    let mut deferred_rewards = state.deferred_rewards;
+    if bond_type == BondType::BondRewards {
+        let rewards = payment.amount + state.deferred_rewards;
+        bonded_amount = rewards;
+        let actual_supply = total_supply + requested_with_fee;
+        if let Some(max_increase) = params.max_exchange_rate_increase {
+            if !actual_supply.is_zero() {
+                let max_rate = Decimal256::from(state.statom_exchange_rate)
+                    * (Decimal256::one() + max_increase.into());
+                let max_underlying = Uint256::from(actual_supply) * max_rate;
+                let total_underlying =
+                    Uint256::from(state.total_bond_statom_amount + state.liquidity_buffer);
+                if max_underlying < total_underlying + Uint256::from(rewards) {
+                    bonded_amount = if max_underlying > total_underlying {
+                        (max_underlying - total_underlying).into()
+                    } else {
+                        Uint128::zero()
+                    };
+                }
+            }
+        }
+        deferred_rewards = rewards.checked_sub(bonded_amount)?;
     }
 
-    let delegations = calculate_delegations(payment.amount, validators.as_slice())?;
+    // the liquidity buffer for instant unbonding is topped up before delegating
+    let buffered_amount = params
+        .liquidity_buffer_target
+        .saturating_sub(state.liquidity_buffer)
+        .min(bonded_amount);
+    let delegated_amount = bonded_amount.checked_sub(buffered_amount)?;
 
-    let mut external_call_msgs: Vec<cosmwasm_std::CosmosMsg> = vec![];
-    for i in 0..delegations.len() {
-        if delegations[i].is_zero() {
-            continue;
+    // exchange rate should be updated for future
+    let new_state = STATE.update(deps.storage, |mut prev_state| -> StdResult<_> {
+        prev_state.total_bond_statom_amount += delegated_amount;
+        prev_state.liquidity_buffer += buffered_amount;
+        // the buffered and deferred coins stay on the hub balance and must not be taken
+        // as unbonded ones
+        prev_state.prev_hub_balance =
+            (prev_state.prev_hub_balance + buffered_amount + deferred_rewards)
+                .checked_sub(prev_state.deferred_rewards)?;
+        prev_state.deferred_rewards = deferred_rewards;
+        if bond_type == BondType::BondRewards {
+            prev_state.update_statom_exchange_rate(total_supply, requested_with_fee);
         }
-        external_call_msgs.push(cosmwasm_std::CosmosMsg::Staking(StakingMsg::Delegate {
-            validator: validators[i].address.clone(),
-            amount: Coin::new(delegations[i].u128(), payment.denom.as_str()),
-        }));
+        Ok(prev_state)
+    })?;
+    if bond_type == BondType::BondRewards {
+        store_exchange_rate_snapshot(deps.storage, &env, &new_state, total_supply)?;
+    }
+
+    let mut external_call_msgs: Vec<cosmwasm_std::CosmosMsg> = insurance_msgs;
+    if !delegated_amount.is_zero() {
+        external_call_msgs.append(&mut delegate_to_validators(
+            &mut deps,
+            delegated_amount,
+            payment.denom.as_str(),
+        )?);
     }
 
     // we don't need to mint stAtom when bonding rewards
@@ -135,33 +228,53 @@ pub fn execute_bond(
             .add_attributes(vec![
                 attr("action", "bond_rewards"),
                 attr("from", sender),
-                attr("bonded", payment.amount),
+                attr("bonded", bonded_amount),
+                attr("buffered", buffered_amount),
+                attr("deferred", deferred_rewards),
             ]);
         return Ok(res);
     }
 
-    let mint_msg = Cw20ExecuteMsg::Mint {
-        recipient: sender.to_string(),
-        amount: mint_amount,
-    };
+    let minted = mint_amount.checked_sub(dead_shares)?;
 
     let token_address = config
         .statom_token_contract
         .ok_or_else(|| StdError::generic_err("the token contract must have been registered"))?;
 
-    external_call_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: token_address.to_string(),
-        msg: to_binary(&mint_msg)?,
-        funds: vec![],
-    }));
+    if !minted.is_zero() {
+        external_call_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: sender.to_string(),
+                amount: minted,
+            })?,
+            funds: vec![],
+        }));
+    }
+    if !dead_shares.is_zero() {
+        external_call_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: dead_shares,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let mut attrs = vec![
+        attr("action", "mint"),
+        attr("from", sender),
+        attr("bonded", payment.amount),
+        attr("buffered", buffered_amount),
+        attr("minted", minted),
+    ];
+    if !dead_shares.is_zero() {
+        attrs.push(attr("dead_shares", dead_shares));
+    }
 
     let res = Response::new()
         .add_messages(external_call_msgs)
-        .add_attributes(vec![
-            attr("action", "mint"),
-            attr("from", sender),
-            attr("bonded", payment.amount),
-            attr("minted", mint_amount),
-        ]);
+        .add_attributes(attrs);
     Ok(res)
 }
@@ -11,30 +11,100 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-
-use crate::state::{CONFIG, PARAMETERS};
-use basset::hub::Parameters;
+use crate::state::{store_pending_change, CONFIG, GUARDIANS, PARAMETERS, PENDING_CHANGES};
+use basset::access_control::{ensure_owner_or_role, Role};
+use basset::hub::{Parameters, PendingChange, PendingChangeMsg};
 use cosmwasm_std::{
-    attr, CosmosMsg, DepsMut, DistributionMsg, Env, MessageInfo, Response, StdError, StdResult,
+    attr, CosmosMsg, Decimal, DepsMut, DistributionMsg, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
 };
+use cw_storage_plus::U64Key;
 
 /// Update general parameters
-/// Only creator/owner is allowed to execute
+/// Only creator/owner or params admins are allowed to execute
 #[allow(clippy::too_many_arguments)]
 pub fn execute_update_params(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     epoch_period: Option<u64>,
     unbonding_period: Option<u64>,
+    liquidity_buffer_target: Option<Uint128>,
+    instant_unbond_fee: Option<Decimal>,
+    max_insurance_cover: Option<Uint128>,
+    min_bond_amount: Option<Uint128>,
+    max_exchange_rate_increase: Option<Decimal>,
+    timelock_period: Option<u64>,
+    max_guardian_pause_duration: Option<u64>,
+    guardian_pause_cooldown: Option<u64>,
 ) -> StdResult<Response> {
-    // only owner can send this message.
     let config = CONFIG.load(deps.storage)?;
-    let sender_raw = info.sender;
-    if sender_raw != config.creator {
-        return Err(StdError::generic_err("unauthorized"));
+    ensure_owner_or_role(
+        deps.storage,
+        &config.creator,
+        &info.sender,
+        Role::ParamsAdmin,
+    )?;
+
+    if let Some(fee) = instant_unbond_fee {
+        if fee > Decimal::one() {
+            return Err(StdError::generic_err(
+                "instant_unbond_fee must not be greater than 1",
+            ));
+        }
+    }
+    if max_guardian_pause_duration == Some(0) {
+        return Err(StdError::generic_err(
+            "max_guardian_pause_duration must be greater than 0",
+        ));
+    }
+
+    let params: Parameters = PARAMETERS.load(deps.storage)?;
+    if params.timelock_period > 0 {
+        let msg = PendingChangeMsg::UpdateParams {
+            epoch_period,
+            unbonding_period,
+            liquidity_buffer_target,
+            instant_unbond_fee,
+            max_insurance_cover,
+            min_bond_amount,
+            max_exchange_rate_increase,
+            timelock_period,
+            max_guardian_pause_duration,
+            guardian_pause_cooldown,
+        };
+        return enqueue_change(deps, env, params.timelock_period, msg);
     }
 
+    update_params(
+        deps,
+        epoch_period,
+        unbonding_period,
+        liquidity_buffer_target,
+        instant_unbond_fee,
+        max_insurance_cover,
+        min_bond_amount,
+        max_exchange_rate_increase,
+        timelock_period,
+        max_guardian_pause_duration,
+        guardian_pause_cooldown,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_params(
+    deps: DepsMut,
+    epoch_period: Option<u64>,
+    unbonding_period: Option<u64>,
+    liquidity_buffer_target: Option<Uint128>,
+    instant_unbond_fee: Option<Decimal>,
+    max_insurance_cover: Option<Uint128>,
+    min_bond_amount: Option<Uint128>,
+    max_exchange_rate_increase: Option<Decimal>,
+    timelock_period: Option<u64>,
+    max_guardian_pause_duration: Option<u64>,
+    guardian_pause_cooldown: Option<u64>,
+) -> StdResult<Response> {
     let params: Parameters = PARAMETERS.load(deps.storage)?;
 
     let new_params = Parameters {
@@ -42,6 +112,23 @@ pub fn execute_update_params(
         underlying_coin_denom: params.underlying_coin_denom,
         unbonding_period: unbonding_period.unwrap_or(params.unbonding_period),
         paused: params.paused,
+        liquidity_buffer_target: liquidity_buffer_target.unwrap_or(params.liquidity_buffer_target),
+        instant_unbond_fee: instant_unbond_fee.unwrap_or(params.instant_unbond_fee),
+        max_insurance_cover: max_insurance_cover.unwrap_or(params.max_insurance_cover),
+        min_bond_amount: min_bond_amount.unwrap_or(params.min_bond_amount),
+        min_total_supply: params.min_total_supply,
+        max_exchange_rate_increase: match max_exchange_rate_increase {
+            Some(max_increase) if max_increase == Decimal::zero() => None,
+            Some(max_increase) => Some(max_increase),
+            None => params.max_exchange_rate_increase,
+        },
+        timelock_period: timelock_period.unwrap_or(params.timelock_period),
+        paused_operations: params.paused_operations,
+        max_guardian_pause_duration: max_guardian_pause_duration
+            .unwrap_or(params.max_guardian_pause_duration),
+        guardian_pause_cooldown: guardian_pause_cooldown.unwrap_or(params.guardian_pause_cooldown),
+        pause_info: params.pause_info,
+        operation_pauses: params.operation_pauses,
     };
 
     PARAMETERS.save(deps.storage, &new_params)?;
@@ -51,16 +138,16 @@ pub fn execute_update_params(
 }
 
 #[allow(clippy::too_many_arguments)]
-/// Update the config. Update the owner, reward and token contracts.
+/// Update the config. Update the reward, registry, token and claims contracts.
 /// Only creator/owner is allowed to execute
 pub fn execute_update_config(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    owner: Option<String>,
     rewards_dispatcher_contract: Option<String>,
     statom_token_contract: Option<String>,
     validators_registry_contract: Option<String>,
+    claims_nft_contract: Option<String>,
 ) -> StdResult<Response> {
     // only owner must be able to send this message.
     let conf = CONFIG.load(deps.storage)?;
@@ -69,16 +156,54 @@ pub fn execute_update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-
-    if let Some(o) = owner {
-        let owner_raw = deps.api.addr_validate(&o)?;
+    let params: Parameters = PARAMETERS.load(deps.storage)?;
+    if params.timelock_period > 0 {
+        // the addresses are checked now, so the change can't turn out to be invalid later
+        for addr in [
+            &rewards_dispatcher_contract,
+            &statom_token_contract,
+            &validators_registry_contract,
+            &claims_nft_contract,
+        ]
+        .iter()
+        .copied()
+        .flatten()
+        {
+            deps.api.addr_validate(addr)?;
+        }
+        if statom_token_contract.is_some() && conf.statom_token_contract.is_some() {
+            return Err(StdError::generic_err(
+                "updating stAtom token address is forbidden",
+            ));
+        }
 
-        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
-            last_config.creator = owner_raw;
-            Ok(last_config)
-        })?;
+        let msg = PendingChangeMsg::UpdateConfig {
+            rewards_dispatcher_contract,
+            validators_registry_contract,
+            statom_token_contract,
+            claims_nft_contract,
+        };
+        return enqueue_change(deps, env, params.timelock_period, msg);
     }
+
+    update_config(
+        deps,
+        rewards_dispatcher_contract,
+        statom_token_contract,
+        validators_registry_contract,
+        claims_nft_contract,
+    )
+}
+
+fn update_config(
+    deps: DepsMut,
+    rewards_dispatcher_contract: Option<String>,
+    statom_token_contract: Option<String>,
+    validators_registry_contract: Option<String>,
+    claims_nft_contract: Option<String>,
+) -> StdResult<Response> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+
     if let Some(reward) = rewards_dispatcher_contract {
         let reward_raw = deps.api.addr_validate(&reward)?;
 
@@ -116,8 +241,132 @@ pub fn execute_update_config(
         })?;
     }
 
+    if let Some(claims_nft) = claims_nft_contract {
+        let claims_nft_raw = deps.api.addr_validate(&claims_nft)?;
+        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
+            last_config.claims_nft_contract = Some(claims_nft_raw);
+            Ok(last_config)
+        })?;
+    }
+
     let res = Response::new()
         .add_messages(messages)
         .add_attributes(vec![attr("action", "update_config")]);
     Ok(res)
 }
+
+fn enqueue_change(
+    deps: DepsMut,
+    env: Env,
+    timelock_period: u64,
+    msg: PendingChangeMsg,
+) -> StdResult<Response> {
+    let execute_after = env
+        .block
+        .time
+        .seconds()
+        .checked_add(timelock_period)
+        .ok_or_else(|| StdError::generic_err("timelock_period is too large"))?;
+    let id = store_pending_change(
+        deps.storage,
+        PendingChange {
+            id: 0,
+            msg,
+            execute_after,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "enqueue_change"),
+        attr("id", id.to_string()),
+        attr("execute_after", execute_after.to_string()),
+    ]))
+}
+
+/// Apply the pending change once its timelock period has passed.
+/// Anyone is allowed to execute
+pub fn execute_pending_change(deps: DepsMut, env: Env, id: u64) -> StdResult<Response> {
+    let change = PENDING_CHANGES
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or_else(|| StdError::generic_err(format!("pending change {} not found", id)))?;
+    if env.block.time.seconds() < change.execute_after {
+        return Err(StdError::generic_err(format!(
+            "pending change {} can't be executed before {}",
+            id, change.execute_after
+        )));
+    }
+
+    PENDING_CHANGES.remove(deps.storage, U64Key::new(id));
+
+    let res = match change.msg {
+        PendingChangeMsg::UpdateParams {
+            epoch_period,
+            unbonding_period,
+            liquidity_buffer_target,
+            instant_unbond_fee,
+            max_insurance_cover,
+            min_bond_amount,
+            max_exchange_rate_increase,
+            timelock_period,
+            max_guardian_pause_duration,
+            guardian_pause_cooldown,
+        } => update_params(
+            deps,
+            epoch_period,
+            unbonding_period,
+            liquidity_buffer_target,
+            instant_unbond_fee,
+            max_insurance_cover,
+            min_bond_amount,
+            max_exchange_rate_increase,
+            timelock_period,
+            max_guardian_pause_duration,
+            guardian_pause_cooldown,
+        )?,
+        PendingChangeMsg::UpdateConfig {
+            rewards_dispatcher_contract,
+            validators_registry_contract,
+            statom_token_contract,
+            claims_nft_contract,
+        } => update_config(
+            deps,
+            rewards_dispatcher_contract,
+            statom_token_contract,
+            validators_registry_contract,
+            claims_nft_contract,
+        )?,
+    };
+
+    Ok(res.add_attributes(vec![
+        attr("action", "execute_pending_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+/// Drop the pending change.
+/// Only the owner or guardians are allowed to execute
+pub fn execute_veto_pending_change(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if !(info.sender == config.creator || GUARDIANS.has(deps.storage, info.sender.to_string())) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if !PENDING_CHANGES.has(deps.storage, U64Key::new(id)) {
+        return Err(StdError::generic_err(format!(
+            "pending change {} not found",
+            id
+        )));
+    }
+    PENDING_CHANGES.remove(deps.storage, U64Key::new(id));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "veto_pending_change"),
+        attr("id", id.to_string()),
+        attr("from", info.sender),
+    ]))
+}
@@ -17,28 +17,54 @@ use cosmwasm_std::entry_point;
 use std::string::FromUtf8Error;
 
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, DistributionMsg,
-    Env, MessageInfo, Order, QueryRequest, Response, StakingMsg, StdError, StdResult, Uint128,
-    WasmMsg, WasmQuery,
+    attr, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    DistributionMsg, Env, MessageInfo, Order, QueryRequest, Response, StakingMsg, StdError,
+    StdResult, Storage, Uint128, WasmMsg, WasmQuery,
 };
 
-use crate::config::{execute_update_config, execute_update_params};
+use crate::config::{
+    execute_pending_change, execute_update_config, execute_update_params,
+    execute_veto_pending_change,
+};
 use crate::state::{
-    all_unbond_history, get_unbond_requests, query_get_finished_amount, CONFIG, CURRENT_BATCH,
-    GUARDIANS, PARAMETERS, STATE,
+    all_unbond_history, decrease_book_delegation, exchange_rate_history,
+    exchange_rate_snapshots_since, get_unbond_requests, increase_book_delegation, load_parameters,
+    pending_changes, query_get_finished_amount, read_unbond_history, slashing_events,
+    store_exchange_rate_snapshot, store_slashing_event, CONFIG, CURRENT_BATCH, DELEGATIONS_BOOK,
+    GUARDIANS, GUARDIAN_PAUSE_ENDS, PARAMETERS, SLASHING_EVENTS, STATE, UNBOND_CLAIMS,
+};
+use crate::unbond::{
+    execute_instant_unbond_statom, execute_process_withdraw_rate, execute_set_withdraw_opt_out,
+    execute_submit_batch, execute_unbond_statom, execute_withdraw_claim, execute_withdraw_unbonded,
+    execute_withdraw_unbonded_for,
 };
-use crate::unbond::{execute_unbond_statom, execute_withdraw_unbonded};
 
-use crate::bond::execute_bond;
+use crate::bond::{delegate_to_validators, execute_bond};
+use basset::access_control::{
+    ensure_owner_or_role, grant_role, has_role, query_roles, revoke_role, Role,
+};
 use basset::hub::{
-    AllHistoryResponse, BondType, Config, ConfigResponse, CurrentBatch, CurrentBatchResponse,
-    InstantiateMsg, MigrateMsg, Parameters, QueryMsg, State, StateResponse, UnbondHistoryResponse,
-    UnbondRequestsResponse, WithdrawableUnbondedResponse,
+    AllHistoryResponse, AprResponse, BondType, Config, ConfigResponse, CurrentBatch,
+    CurrentBatchResponse, ExchangeRateHistoryResponse, InstantiateMsg, MigrateMsg, OperationPause,
+    Parameters, PausableOperation, PauseInfo, PauseStateResponse, PendingChangesResponse, QueryMsg,
+    SlashingEvent, SlashingEventsResponse, State, StateResponse, UnbondClaimResponse,
+    UnbondHistoryResponse, UnbondRequestDetails, UnbondRequestStatus,
+    UnbondRequestsDetailedResponse, UnbondRequestsResponse, ValidatorSlashing,
+    WithdrawableUnbondedResponse,
+};
+use basset::hub::{Cw20HookMsg, Cw721HookMsg, Cw721ReceiveMsg, ExecuteMsg};
+use basset::ownership::{
+    accept_ownership, cancel_ownership_proposal, propose_new_owner, query_pending_owner,
 };
-use basset::hub::{Cw20HookMsg, ExecuteMsg};
+use cosmwasm_bignumber::Decimal256;
 use cw20::{Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
+use cw_storage_plus::U64Key;
 use lido_cosmos_rewards_dispatcher::msg::ExecuteMsg::DispatchRewards;
 
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+const DEFAULT_MAX_GUARDIAN_PAUSE_DURATION: u64 = 3 * 24 * 60 * 60;
+const DEFAULT_GUARDIAN_PAUSE_COOLDOWN: u64 = 24 * 60 * 60;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -54,6 +80,7 @@ pub fn instantiate(
         reward_dispatcher_contract: None,
         validators_registry_contract: None,
         statom_token_contract: None,
+        claims_nft_contract: None,
     };
     CONFIG.save(deps.storage, &data)?;
 
@@ -73,6 +100,18 @@ pub fn instantiate(
         underlying_coin_denom: msg.underlying_coin_denom,
         unbonding_period: msg.unbonding_period,
         paused: Some(false),
+        liquidity_buffer_target: Uint128::zero(),
+        instant_unbond_fee: Decimal::zero(),
+        max_insurance_cover: Uint128::zero(),
+        min_bond_amount: Uint128::zero(),
+        min_total_supply: msg.min_total_supply,
+        max_exchange_rate_increase: None,
+        timelock_period: 0,
+        paused_operations: 0,
+        max_guardian_pause_duration: DEFAULT_MAX_GUARDIAN_PAUSE_DURATION,
+        guardian_pause_cooldown: DEFAULT_GUARDIAN_PAUSE_COOLDOWN,
+        pause_info: None,
+        operation_pauses: vec![],
     };
 
     PARAMETERS.save(deps.storage, &params)?;
@@ -91,39 +130,109 @@ pub fn instantiate(
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ReceiveNft(msg) => receive_nft(deps, env, info, msg),
         ExecuteMsg::BondForStAtom {} => execute_bond(deps, env, info, BondType::StAtom),
         ExecuteMsg::BondRewards {} => execute_bond(deps, env, info, BondType::BondRewards),
         ExecuteMsg::DispatchRewards {} => execute_dispatch_rewards(deps, env, info),
-        ExecuteMsg::WithdrawUnbonded {} => execute_withdraw_unbonded(deps, env, info),
+        ExecuteMsg::WithdrawUnbonded {
+            recipient,
+            batch_ids,
+            limit,
+        } => execute_withdraw_unbonded(deps, env, info, recipient, batch_ids, limit),
+        ExecuteMsg::ProcessWithdrawRate { limit } => {
+            execute_process_withdraw_rate(deps, env, limit)
+        }
+        ExecuteMsg::SubmitBatch {} => execute_submit_batch(deps, env),
+        ExecuteMsg::WithdrawUnbondedFor { addresses, limit } => {
+            execute_withdraw_unbonded_for(deps, env, addresses, limit)
+        }
+        ExecuteMsg::SetWithdrawOptOut { opt_out } => {
+            execute_set_withdraw_opt_out(deps, env, info, opt_out)
+        }
         ExecuteMsg::CheckSlashing {} => execute_slashing(deps, env),
+        ExecuteMsg::FundInsurance {} => execute_fund_insurance(deps, env, info),
         ExecuteMsg::UpdateParams {
             epoch_period,
             unbonding_period,
-        } => execute_update_params(deps, env, info, epoch_period, unbonding_period),
+            liquidity_buffer_target,
+            instant_unbond_fee,
+            max_insurance_cover,
+            min_bond_amount,
+            max_exchange_rate_increase,
+            timelock_period,
+            max_guardian_pause_duration,
+            guardian_pause_cooldown,
+        } => execute_update_params(
+            deps,
+            env,
+            info,
+            epoch_period,
+            unbonding_period,
+            liquidity_buffer_target,
+            instant_unbond_fee,
+            max_insurance_cover,
+            min_bond_amount,
+            max_exchange_rate_increase,
+            timelock_period,
+            max_guardian_pause_duration,
+            guardian_pause_cooldown,
+        ),
         ExecuteMsg::UpdateConfig {
-            owner,
             rewards_dispatcher_contract,
             validators_registry_contract,
             statom_token_contract,
+            claims_nft_contract,
         } => execute_update_config(
             deps,
             env,
             info,
-            owner,
             rewards_dispatcher_contract,
             statom_token_contract,
             validators_registry_contract,
+            claims_nft_contract,
         ),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let conf = CONFIG.load(deps.storage)?;
+            propose_new_owner(deps, env, info, &conf.creator, owner, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info, |deps, owner| {
+            CONFIG.update(deps.storage, |mut conf| -> StdResult<_> {
+                conf.creator = owner;
+                Ok(conf)
+            })?;
+            Ok(())
+        }),
+        ExecuteMsg::CancelOwnershipProposal {} => {
+            let conf = CONFIG.load(deps.storage)?;
+            cancel_ownership_proposal(deps, info, &conf.creator)
+        }
+        ExecuteMsg::GrantRole { role, address } => {
+            let conf = CONFIG.load(deps.storage)?;
+            grant_role(deps, info, &conf.creator, role, address)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let conf = CONFIG.load(deps.storage)?;
+            revoke_role(deps, info, &conf.creator, role, address)
+        }
         ExecuteMsg::RedelegateProxy {
             src_validator,
             redelegations,
         } => execute_redelegate_proxy(deps, env, info, src_validator, redelegations),
-        ExecuteMsg::PauseContracts {} => execute_pause_contracts(deps, env, info),
+        ExecuteMsg::PauseContracts { reason } => execute_pause_contracts(deps, env, info, reason),
+        ExecuteMsg::ExtendPause { expires_at } => execute_extend_pause(deps, env, info, expires_at),
         ExecuteMsg::UnpauseContracts {} => execute_unpause_contracts(deps, env, info),
+        ExecuteMsg::PauseOperations { operations } => {
+            execute_pause_operations(deps, env, info, operations)
+        }
+        ExecuteMsg::UnpauseOperations { operations } => {
+            execute_unpause_operations(deps, env, info, operations)
+        }
         ExecuteMsg::AddGuardians { addresses } => execute_add_guardians(deps, env, info, addresses),
         ExecuteMsg::RemoveGuardians { addresses } => {
             execute_remove_guardians(deps, env, info, addresses)
         }
+        ExecuteMsg::ExecutePendingChange { id } => execute_pending_change(deps, env, id),
+        ExecuteMsg::VetoPendingChange { id } => execute_veto_pending_change(deps, env, info, id),
     }
 }
 
@@ -167,33 +276,138 @@ pub fn execute_remove_guardians(
         .add_attributes(guardians.iter().map(|g| attr("value", g))))
 }
 
-pub fn execute_pause_contracts(deps: DepsMut, _env: Env, info: MessageInfo) -> StdResult<Response> {
+/// Pause the contracts. The pause of a guardian or a pauser expires after
+/// max_guardian_pause_duration and they can't pause again until the cooldown has passed
+pub fn execute_pause_contracts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reason: Option<String>,
+) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?;
-    if !(info.sender == config.creator || GUARDIANS.has(deps.storage, info.sender.to_string())) {
+    let is_owner = info.sender == config.creator;
+    if !(is_owner
+        || GUARDIANS.has(deps.storage, info.sender.to_string())
+        || has_role(deps.storage, Role::Pauser, &info.sender))
+    {
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let mut params: Parameters = PARAMETERS.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let mut params: Parameters = load_parameters(deps.storage, &env)?;
+
+    let mut expires_at = None;
+    if !is_owner {
+        expires_at = Some(start_guardian_pause(
+            deps.storage,
+            &info.sender,
+            &params,
+            now,
+        )?);
+    }
+
+    // a pause in effect is never shortened
+    if params.paused.unwrap_or(false) {
+        let current_expiry = params.pause_info.as_ref().and_then(|info| info.expires_at);
+        expires_at = match (current_expiry, expires_at) {
+            (Some(current), Some(new)) => Some(current.max(new)),
+            _ => None,
+        };
+    }
+
     params.paused = Some(true);
+    params.pause_info = Some(PauseInfo {
+        initiator: info.sender.clone(),
+        reason,
+        paused_at: now,
+        expires_at,
+    });
 
     PARAMETERS.save(deps.storage, &params)?;
 
-    let res = Response::new().add_attributes(vec![attr("action", "pause_contracts")]);
-    Ok(res)
+    let mut attrs = vec![
+        attr("action", "pause_contracts"),
+        attr("initiator", info.sender),
+    ];
+    if let Some(expires_at) = expires_at {
+        attrs.push(attr("expires_at", expires_at.to_string()));
+    }
+    Ok(Response::new().add_attributes(attrs))
 }
 
-pub fn execute_unpause_contracts(
+/// Check that the cooldown of the guardian or pauser has passed and record the end of its
+/// new pause. Return the time the pause expires at
+fn start_guardian_pause(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    params: &Parameters,
+    now: u64,
+) -> StdResult<u64> {
+    if let Some(pause_end) = GUARDIAN_PAUSE_ENDS.may_load(storage, sender.as_str())? {
+        let next_pause = pause_end + params.guardian_pause_cooldown;
+        if now < next_pause {
+            return Err(StdError::generic_err(format!(
+                "the contracts can't be paused by {} again before {}",
+                sender, next_pause
+            )));
+        }
+    }
+    let pause_end = now + params.max_guardian_pause_duration;
+    GUARDIAN_PAUSE_ENDS.save(storage, sender.as_str(), &pause_end)?;
+
+    Ok(pause_end)
+}
+
+/// Set the time the paused contracts resume at.
+/// Only the owner is allowed to execute
+pub fn execute_extend_pause(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
+    expires_at: Option<u64>,
 ) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?;
     if info.sender != config.creator {
         return Err(StdError::generic_err("unauthorized"));
     }
 
+    let mut params: Parameters = load_parameters(deps.storage, &env)?;
+    if !params.paused.unwrap_or(false) {
+        return Err(StdError::generic_err("the contracts are not paused"));
+    }
+
+    let now = env.block.time.seconds();
+    let pause_info = params.pause_info.get_or_insert(PauseInfo {
+        initiator: info.sender,
+        reason: None,
+        paused_at: now,
+        expires_at: None,
+    });
+    pause_info.expires_at = expires_at;
+
+    PARAMETERS.save(deps.storage, &params)?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "extend_pause"),
+        attr(
+            "expires_at",
+            expires_at.map_or_else(|| String::from("none"), |e| e.to_string()),
+        ),
+    ]);
+    Ok(res)
+}
+
+pub fn execute_unpause_contracts(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner_or_role(deps.storage, &config.creator, &info.sender, Role::Unpauser)?;
+
     let mut params: Parameters = PARAMETERS.load(deps.storage)?;
     params.paused = Some(false);
+    params.pause_info = None;
 
     PARAMETERS.save(deps.storage, &params)?;
 
@@ -201,6 +415,101 @@ pub fn execute_unpause_contracts(
     Ok(res)
 }
 
+/// Pause the given operations. The pause of a guardian or a pauser expires after
+/// max_guardian_pause_duration and shares the cooldown of the pause of the contracts
+pub fn execute_pause_operations(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operations: Vec<PausableOperation>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_owner = info.sender == config.creator;
+    if !(is_owner
+        || GUARDIANS.has(deps.storage, info.sender.to_string())
+        || has_role(deps.storage, Role::Pauser, &info.sender))
+    {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let now = env.block.time.seconds();
+    let mut params: Parameters = load_parameters(deps.storage, &env)?;
+
+    let mut expires_at = None;
+    if !is_owner {
+        expires_at = Some(start_guardian_pause(
+            deps.storage,
+            &info.sender,
+            &params,
+            now,
+        )?);
+    }
+
+    for operation in &operations {
+        // a pause in effect is never shortened
+        let mut operation_expires_at = expires_at;
+        if params.paused_operations & operation.mask() != 0 {
+            let current_expiry = params
+                .operation_pauses
+                .iter()
+                .find(|pause| pause.operation == *operation)
+                .map(|pause| pause.expires_at);
+            operation_expires_at = match (current_expiry, expires_at) {
+                (Some(current), Some(new)) => Some(current.max(new)),
+                _ => None,
+            };
+        }
+
+        params.paused_operations |= operation.mask();
+        params
+            .operation_pauses
+            .retain(|pause| pause.operation != *operation);
+        if let Some(expires_at) = operation_expires_at {
+            params.operation_pauses.push(OperationPause {
+                operation: *operation,
+                expires_at,
+            });
+        }
+    }
+
+    PARAMETERS.save(deps.storage, &params)?;
+
+    let mut attrs = vec![
+        attr("action", "pause_operations"),
+        attr("paused_operations", params.paused_operations.to_string()),
+    ];
+    if let Some(expires_at) = expires_at {
+        attrs.push(attr("expires_at", expires_at.to_string()));
+    }
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn execute_unpause_operations(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    operations: Vec<PausableOperation>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner_or_role(deps.storage, &config.creator, &info.sender, Role::Unpauser)?;
+
+    let mut params: Parameters = PARAMETERS.load(deps.storage)?;
+    for operation in &operations {
+        params.paused_operations &= !operation.mask();
+        params
+            .operation_pauses
+            .retain(|pause| pause.operation != *operation);
+    }
+
+    PARAMETERS.save(deps.storage, &params)?;
+
+    let res = Response::new().add_attributes(vec![
+        attr("action", "unpause_operations"),
+        attr("paused_operations", params.paused_operations.to_string()),
+    ]);
+    Ok(res)
+}
+
 pub fn execute_redelegate_proxy(
     deps: DepsMut,
     _env: Env,
@@ -220,16 +529,16 @@ pub fn execute_redelegate_proxy(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let messages: Vec<CosmosMsg> = redelegations
-        .into_iter()
-        .map(|(dst_validator, amount)| {
-            cosmwasm_std::CosmosMsg::Staking(StakingMsg::Redelegate {
-                src_validator: src_validator.clone(),
-                dst_validator,
-                amount,
-            })
-        })
-        .collect();
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (dst_validator, amount) in redelegations {
+        decrease_book_delegation(deps.storage, &src_validator, amount.amount)?;
+        increase_book_delegation(deps.storage, &dst_validator, amount.amount)?;
+        messages.push(cosmwasm_std::CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator: src_validator.clone(),
+            dst_validator,
+            amount,
+        }));
+    }
 
     let res = Response::new().add_messages(messages);
 
@@ -243,8 +552,8 @@ pub fn receive_cw20(
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> StdResult<Response> {
-    let params: Parameters = PARAMETERS.load(deps.storage)?;
-    if params.paused.unwrap_or(false) {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Unbond) {
         return Err(StdError::generic_err("the contract is temporarily paused"));
     }
 
@@ -262,13 +571,55 @@ pub fn receive_cw20(
     };
 
     match from_binary(&cw20_msg.msg)? {
-        Cw20HookMsg::Unbond {} => {
+        Cw20HookMsg::Unbond {
+            recipient,
+            mint_claim,
+        } => {
             if contract_addr == statom_contract_addr {
-                execute_unbond_statom(deps, env, cw20_msg.amount, cw20_msg.sender)
+                execute_unbond_statom(
+                    deps,
+                    env,
+                    cw20_msg.amount,
+                    cw20_msg.sender,
+                    recipient,
+                    mint_claim,
+                )
             } else {
                 Err(StdError::generic_err("unauthorized"))
             }
         }
+        Cw20HookMsg::InstantUnbond {} => {
+            if contract_addr == statom_contract_addr {
+                execute_instant_unbond_statom(deps, env, cw20_msg.amount, cw20_msg.sender)
+            } else {
+                Err(StdError::generic_err("unauthorized"))
+            }
+        }
+    }
+}
+
+/// CW721 token receive handler.
+pub fn receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+) -> StdResult<Response> {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Withdraw) {
+        return Err(StdError::generic_err("the contract is temporarily paused"));
+    }
+
+    // only claims contract can execute this message
+    let conf = CONFIG.load(deps.storage)?;
+    if conf.claims_nft_contract != Some(info.sender) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    match from_binary(&nft_msg.msg)? {
+        Cw721HookMsg::WithdrawUnbonded { recipient } => {
+            execute_withdraw_claim(deps, nft_msg.token_id, nft_msg.sender, recipient)
+        }
     }
 }
 
@@ -278,8 +629,8 @@ pub fn execute_dispatch_rewards(
     env: Env,
     _info: MessageInfo,
 ) -> StdResult<Response> {
-    let params: Parameters = PARAMETERS.load(deps.storage)?;
-    if params.paused.unwrap_or(false) {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Rewards) {
         return Err(StdError::generic_err("the contract is temporarily paused"));
     }
 
@@ -288,6 +639,13 @@ pub fn execute_dispatch_rewards(
         .reward_dispatcher_contract
         .ok_or_else(|| StdError::generic_err("the reward contract must have been registered"))?;
 
+    // Rewards of every validator are passed to the dispatcher to pay the operator fee
+    let validator_rewards = query_validator_rewards(
+        deps.as_ref(),
+        env.contract.address.to_string(),
+        &params.underlying_coin_denom,
+    )?;
+
     // Send withdraw message
     let mut withdraw_msgs = withdraw_all_rewards(&deps, env.contract.address.to_string())?;
     let mut messages: Vec<CosmosMsg> = vec![];
@@ -295,7 +653,7 @@ pub fn execute_dispatch_rewards(
 
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: reward_addr_dispatcher.to_string(),
-        msg: to_binary(&DispatchRewards {})?,
+        msg: to_binary(&DispatchRewards { validator_rewards })?,
         funds: vec![],
     }));
 
@@ -324,6 +682,36 @@ fn withdraw_all_rewards(deps: &DepsMut, delegator: String) -> StdResult<Vec<Cosm
     Ok(messages)
 }
 
+/// Returns the accumulated rewards in the underlying denom for every validator with non-zero rewards
+fn query_validator_rewards(
+    deps: Deps,
+    delegator: String,
+    denom: &str,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let mut validator_rewards: Vec<(String, Uint128)> = vec![];
+
+    let delegations = deps.querier.query_all_delegations(delegator.clone())?;
+    for delegation in delegations {
+        let full_delegation = deps
+            .querier
+            .query_delegation(delegator.clone(), delegation.validator.clone())?;
+        let reward: Uint128 = full_delegation
+            .map(|d| {
+                d.accumulated_rewards
+                    .iter()
+                    .filter(|c| c.denom == denom)
+                    .map(|c| c.amount)
+                    .sum()
+            })
+            .unwrap_or_default();
+        if !reward.is_zero() {
+            validator_rewards.push((delegation.validator, reward));
+        }
+    }
+
+    Ok(validator_rewards)
+}
+
 fn query_actual_state(deps: Deps, env: Env) -> StdResult<State> {
     let mut state = STATE.load(deps.storage)?;
     let delegations = deps.querier.query_all_delegations(env.contract.address)?;
@@ -354,7 +742,13 @@ fn query_actual_state(deps: Deps, env: Env) -> StdResult<State> {
     let current_requested_statom = current_batch.requested_statom;
 
     if state.total_bond_statom_amount.u128() > actual_total_bonded.u128() {
-        state.total_bond_statom_amount = actual_total_bonded;
+        // the insurance reserve covers the shortfall up to the cap before it is socialized
+        let cover = (state.total_bond_statom_amount - actual_total_bonded)
+            .min(params.max_insurance_cover)
+            .min(state.insurance_reserve);
+        state.insurance_reserve -= cover;
+        state.prev_hub_balance = state.prev_hub_balance.saturating_sub(cover);
+        state.total_bond_statom_amount = actual_total_bonded + cover;
     }
     state.update_statom_exchange_rate(state.total_statom_issued, current_requested_statom);
     Ok(state)
@@ -362,29 +756,144 @@ fn query_actual_state(deps: Deps, env: Env) -> StdResult<State> {
 
 /// Check whether slashing has happened
 /// This is used for checking slashing while bonding or unbonding
-pub fn slashing(deps: &mut DepsMut, env: Env) -> StdResult<State> {
-    let state = query_actual_state(deps.as_ref(), env)?;
+/// Returns the messages delegating the insurance cover, which must be sent along
+pub fn slashing(deps: &mut DepsMut, env: Env) -> StdResult<(State, Vec<CosmosMsg>)> {
+    let prev_state = STATE.load(deps.storage)?;
+    let state = query_actual_state(deps.as_ref(), env.clone())?;
 
     STATE.save(deps.storage, &state)?;
+    let covered = prev_state.insurance_reserve - state.insurance_reserve;
+    if state.total_bond_statom_amount < prev_state.total_bond_statom_amount || !covered.is_zero() {
+        record_slashing_event(deps, &env, &prev_state, &state, covered)?;
+    }
+    if state.statom_exchange_rate != prev_state.statom_exchange_rate {
+        store_exchange_rate_snapshot(deps.storage, &env, &state, state.total_statom_issued)?;
+    }
 
-    Ok(state)
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !covered.is_zero() {
+        let coin_denom = PARAMETERS.load(deps.storage)?.underlying_coin_denom;
+        messages = delegate_to_validators(deps, covered, &coin_denom)?;
+    }
+
+    Ok((state, messages))
+}
+
+/// Store the detected shortfall with the validators it came from
+/// and bring the delegations book in line with the actual delegations
+fn record_slashing_event(
+    deps: &mut DepsMut,
+    env: &Env,
+    prev_state: &State,
+    state: &State,
+    covered: Uint128,
+) -> StdResult<()> {
+    let coin_denom = PARAMETERS.load(deps.storage)?.underlying_coin_denom;
+    let actual_delegations: Vec<(String, Uint128)> = deps
+        .querier
+        .query_all_delegations(env.contract.address.clone())?
+        .into_iter()
+        .filter(|d| d.amount.denom == coin_denom)
+        .map(|d| (d.validator, d.amount.amount))
+        .collect();
+    let book: Vec<(String, Uint128)> = DELEGATIONS_BOOK
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, amount) = item?;
+            Ok((String::from_utf8(key)?, amount))
+        })
+        .collect::<StdResult<_>>()?;
+
+    let mut validators: Vec<ValidatorSlashing> = vec![];
+    for (validator, expected_delegation) in &book {
+        let actual_delegation = actual_delegations
+            .iter()
+            .find(|(v, _)| v == validator)
+            .map(|(_, amount)| *amount)
+            .unwrap_or_default();
+        if actual_delegation < *expected_delegation {
+            validators.push(ValidatorSlashing {
+                validator: validator.clone(),
+                expected_delegation: *expected_delegation,
+                actual_delegation,
+            });
+        }
+        DELEGATIONS_BOOK.remove(deps.storage, validator);
+    }
+    for (validator, amount) in &actual_delegations {
+        DELEGATIONS_BOOK.save(deps.storage, validator, amount)?;
+    }
+
+    store_slashing_event(
+        deps.storage,
+        SlashingEvent {
+            id: 0,
+            time: env.block.time.seconds(),
+            height: env.block.height,
+            slashed_amount: prev_state.total_bond_statom_amount + covered
+                - state.total_bond_statom_amount,
+            covered_amount: covered,
+            validators,
+            statom_exchange_rate_before: prev_state.statom_exchange_rate,
+            statom_exchange_rate_after: state.statom_exchange_rate,
+        },
+    )?;
+    Ok(())
 }
 
 /// Handler for tracking slashing
 pub fn execute_slashing(mut deps: DepsMut, env: Env) -> StdResult<Response> {
-    let params: Parameters = PARAMETERS.load(deps.storage)?;
+    let params: Parameters = load_parameters(deps.storage, &env)?;
     if params.paused.unwrap_or(false) {
         return Err(StdError::generic_err("the contract is temporarily paused"));
     }
 
     // call slashing and return new exchange rate
-    let state = slashing(&mut deps, env)?;
+    let (state, insurance_msgs) = slashing(&mut deps, env)?;
+    Ok(Response::new()
+        .add_messages(insurance_msgs)
+        .add_attributes(vec![
+            attr("action", "check_slashing"),
+            attr(
+                "new_statom_exchange_rate",
+                state.statom_exchange_rate.to_string(),
+            ),
+        ]))
+}
+
+/// Add the sent underlying to the insurance reserve
+pub fn execute_fund_insurance(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    if params.paused.unwrap_or(false) {
+        return Err(StdError::generic_err("the contract is temporarily paused"));
+    }
+
+    let coin_denom = params.underlying_coin_denom;
+    if info.funds.len() > 1usize {
+        return Err(StdError::generic_err(
+            "More than one coin is sent; only one asset is supported",
+        ));
+    }
+    let payment = info
+        .funds
+        .iter()
+        .find(|x| x.denom == coin_denom && x.amount > Uint128::zero())
+        .ok_or_else(|| {
+            StdError::generic_err(format!("No {} assets are provided to fund", coin_denom))
+        })?;
+
+    let state = STATE.update(deps.storage, |mut state| -> StdResult<State> {
+        state.insurance_reserve += payment.amount;
+        // the reserve stays on the hub balance and must not be taken as unbonded coins
+        state.prev_hub_balance += payment.amount;
+        Ok(state)
+    })?;
+
     Ok(Response::new().add_attributes(vec![
-        attr("action", "check_slashing"),
-        attr(
-            "new_statom_exchange_rate",
-            state.statom_exchange_rate.to_string(),
-        ),
+        attr("action", "fund_insurance"),
+        attr("from", info.sender),
+        attr("amount", payment.amount),
+        attr("insurance_reserve", state.insurance_reserve),
     ]))
 }
 
@@ -397,12 +906,32 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::WithdrawableUnbonded { address } => {
             to_binary(&query_withdrawable_unbonded(deps, address, env)?)
         }
-        QueryMsg::Parameters {} => to_binary(&query_params(deps)?),
+        QueryMsg::Parameters {} => to_binary(&query_params(deps, env)?),
         QueryMsg::UnbondRequests { address } => to_binary(&query_unbond_requests(deps, address)?),
+        QueryMsg::UnbondRequestsDetailed { address } => {
+            to_binary(&query_unbond_requests_detailed(deps, env, address)?)
+        }
         QueryMsg::AllHistory { start_from, limit } => {
             to_binary(&query_unbond_requests_limitation(deps, start_from, limit)?)
         }
         QueryMsg::Guardians => to_binary(&query_guardians(deps)?),
+        QueryMsg::UnbondClaim { token_id } => to_binary(&query_unbond_claim(deps, token_id)?),
+        QueryMsg::ExchangeRateHistory { start_after, limit } => {
+            to_binary(&query_exchange_rate_history(deps, start_after, limit)?)
+        }
+        QueryMsg::Apr { window_seconds } => to_binary(&query_apr(deps, env, window_seconds)?),
+        QueryMsg::SlashingEvent { id } => {
+            to_binary(&SLASHING_EVENTS.load(deps.storage, U64Key::new(id))?)
+        }
+        QueryMsg::SlashingEvents { start_after, limit } => {
+            to_binary(&query_slashing_events(deps, start_after, limit)?)
+        }
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::Roles {} => to_binary(&query_roles(deps)?),
+        QueryMsg::PauseState {} => to_binary(&query_pause_state(deps, env)?),
+        QueryMsg::PendingChanges { start_after, limit } => {
+            to_binary(&query_pending_changes(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -413,6 +942,18 @@ fn query_guardians(deps: Deps) -> StdResult<Vec<String>> {
     Ok(guardians_decoded?)
 }
 
+fn query_pause_state(deps: Deps, env: Env) -> StdResult<PauseStateResponse> {
+    let params: Parameters = load_parameters(deps.storage, &env)?;
+    Ok(PauseStateResponse {
+        paused: params.paused.unwrap_or(false),
+        bond: params.is_paused(PausableOperation::Bond),
+        unbond: params.is_paused(PausableOperation::Unbond),
+        withdraw: params.is_paused(PausableOperation::Withdraw),
+        rewards: params.is_paused(PausableOperation::Rewards),
+        token_transfers: params.is_paused(PausableOperation::TokenTransfers),
+    })
+}
+
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -425,6 +966,7 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         reward_dispatcher_contract: reward_dispatcher,
         validators_registry_contract: validators_contract,
         statom_token_contract: statom_token,
+        claims_nft_contract: config.claims_nft_contract.map(|s| s.into()),
     })
 }
 
@@ -436,6 +978,10 @@ fn query_state(deps: Deps, env: Env) -> StdResult<StateResponse> {
         prev_hub_balance: state.prev_hub_balance,
         last_unbonded_time: state.last_unbonded_time,
         last_processed_batch: state.last_processed_batch,
+        liquidity_buffer: state.liquidity_buffer,
+        insurance_reserve: state.insurance_reserve,
+        deferred_rewards: state.deferred_rewards,
+        unbonding_amount: state.unbonding_amount,
     };
     Ok(res)
 }
@@ -463,8 +1009,8 @@ fn query_withdrawable_unbonded(
     Ok(withdrawable)
 }
 
-fn query_params(deps: Deps) -> StdResult<Parameters> {
-    PARAMETERS.load(deps.storage)
+fn query_params(deps: Deps, env: Env) -> StdResult<Parameters> {
+    load_parameters(deps.storage, &env)
 }
 
 pub(crate) fn query_total_statom_issued(deps: Deps) -> StdResult<Uint128> {
@@ -486,6 +1032,125 @@ fn query_unbond_requests(deps: Deps, address: String) -> StdResult<UnbondRequest
     Ok(res)
 }
 
+fn query_unbond_requests_detailed(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<UnbondRequestsDetailedResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let current_batch = CURRENT_BATCH.load(deps.storage)?;
+    let state = query_actual_state(deps, env.clone())?;
+    let now = env.block.time.seconds();
+
+    let requests = get_unbond_requests(deps.storage, address.clone())?
+        .into_iter()
+        .map(|(batch_id, statom_amount)| {
+            if batch_id == current_batch.id {
+                // the batch is undelegated once the epoch period has passed
+                let submit_time = (state.last_unbonded_time + params.epoch_period + 1).max(now);
+                return Ok(UnbondRequestDetails {
+                    batch_id,
+                    statom_amount,
+                    status: UnbondRequestStatus::Pending,
+                    expected_amount: statom_amount * state.statom_exchange_rate,
+                    release_time: submit_time + params.unbonding_period,
+                });
+            }
+
+            let history = read_unbond_history(deps.storage, batch_id)?;
+            let release_time = history.time + params.unbonding_period;
+            let status = if history.released || release_time <= now {
+                UnbondRequestStatus::Withdrawable
+            } else {
+                UnbondRequestStatus::Unbonding
+            };
+            Ok(UnbondRequestDetails {
+                batch_id,
+                statom_amount,
+                status,
+                expected_amount: statom_amount * history.statom_withdraw_rate,
+                release_time,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(UnbondRequestsDetailedResponse { address, requests })
+}
+
+fn query_exchange_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ExchangeRateHistoryResponse> {
+    let history = exchange_rate_history(deps.storage, start_after, limit)?;
+    Ok(ExchangeRateHistoryResponse { history })
+}
+
+fn query_slashing_events(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SlashingEventsResponse> {
+    let events = slashing_events(deps.storage, start_after, limit)?;
+    Ok(SlashingEventsResponse { events })
+}
+
+fn query_pending_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingChangesResponse> {
+    let changes = pending_changes(deps.storage, start_after, limit)?;
+    Ok(PendingChangesResponse { changes })
+}
+
+fn query_apr(deps: Deps, env: Env, window_seconds: u64) -> StdResult<AprResponse> {
+    let window_start = env.block.time.seconds().saturating_sub(window_seconds);
+    let (first, last) = match exchange_rate_snapshots_since(deps.storage, window_start)? {
+        Some((first, last)) if last.time > first.time => (first, last),
+        _ => {
+            return Ok(AprResponse {
+                apr: None,
+                from_time: None,
+                to_time: None,
+            })
+        }
+    };
+
+    // the yield is not negative, a rate decreased by slashing gives zero
+    let apr = if last.statom_exchange_rate > first.statom_exchange_rate
+        && !first.statom_exchange_rate.is_zero()
+    {
+        let growth = Decimal256::from(last.statom_exchange_rate)
+            / Decimal256::from(first.statom_exchange_rate)
+            - Decimal256::one();
+        growth * Decimal256::from_ratio(SECONDS_PER_YEAR, last.time - first.time)
+    } else {
+        Decimal256::zero()
+    };
+
+    Ok(AprResponse {
+        apr: Some(apr.into()),
+        from_time: Some(first.time),
+        to_time: Some(last.time),
+    })
+}
+
+fn query_unbond_claim(deps: Deps, token_id: String) -> StdResult<UnbondClaimResponse> {
+    let claim = UNBOND_CLAIMS.load(deps.storage, &token_id)?;
+    let (released, withdrawable) = match read_unbond_history(deps.storage, claim.batch_id) {
+        Ok(h) if h.released => (true, claim.statom_amount * h.statom_withdraw_rate),
+        _ => (false, Uint128::zero()),
+    };
+    Ok(UnbondClaimResponse {
+        token_id,
+        batch_id: claim.batch_id,
+        statom_amount: claim.statom_amount,
+        released,
+        withdrawable,
+    })
+}
+
 fn query_unbond_requests_limitation(
     deps: Deps,
     start: Option<u64>,
@@ -513,6 +1178,28 @@ fn query_unbond_requests_limitation(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // the unbonding amount is summed up over the unreleased batches submitted before it was tracked
+    let mut state = STATE.load(deps.storage)?;
+    let current_batch = CURRENT_BATCH.load(deps.storage)?;
+    state.unbonding_amount = Uint128::zero();
+    for batch_id in state.last_processed_batch + 1..current_batch.id {
+        let history = read_unbond_history(deps.storage, batch_id)?;
+        if !history.released {
+            state.unbonding_amount += history.undelegated_amount();
+        }
+    }
+    STATE.save(deps.storage, &state)?;
+
+    // the pauses of the guardians and the pausers used not to expire unless configured
+    let mut params = PARAMETERS.load(deps.storage)?;
+    if params.max_guardian_pause_duration == 0 {
+        params.max_guardian_pause_duration = DEFAULT_MAX_GUARDIAN_PAUSE_DURATION;
+        if params.guardian_pause_cooldown == 0 {
+            params.guardian_pause_cooldown = DEFAULT_GUARDIAN_PAUSE_COOLDOWN;
+        }
+        PARAMETERS.save(deps.storage, &params)?;
+    }
+
     Ok(Response::new())
 }
@@ -12,13 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use cosmwasm_std::{from_slice, to_vec, Order, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{from_slice, to_vec, Env, Order, StdError, StdResult, Storage, Uint128};
 use cosmwasm_storage::{Bucket, PrefixedStorage, ReadonlyBucket, ReadonlyPrefixedStorage};
 
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Bound, Item, Map, U64Key};
 
 use basset::hub::{
-    Config, CurrentBatch, Parameters, State, UnbondHistory, UnbondRequest, UnbondWaitEntity,
+    Config, CurrentBatch, ExchangeRateSnapshot, Parameters, PendingChange, SlashingEvent, State,
+    UnbondClaim, UnbondHistory, UnbondRequest, UnbondWaitEntity,
 };
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -29,6 +30,33 @@ pub const STATE: Item<State> = Item::new("state");
 // Contains whitelisted address which are allowed to pause (but not unpause) the contracts
 pub const GUARDIANS: Map<String, bool> = Map::new("guardians");
 
+// End of the last pause of the contracts initiated by each guardian or pauser
+pub const GUARDIAN_PAUSE_ENDS: Map<&str, u64> = Map::new("guardian_pause_ends");
+
+// Contains addresses which opted out of withdrawals made on their behalf
+pub const WITHDRAW_OPT_OUTS: Map<String, bool> = Map::new("withdraw_opt_outs");
+
+// Unbond requests minted as cw721 claims, keyed by token id
+pub const UNBOND_CLAIMS: Map<&str, UnbondClaim> = Map::new("unbond_claims");
+pub const CLAIMS_COUNT: Item<u64> = Item::new("claims_count");
+
+// Ring buffer of the last MAX_EXCHANGE_RATE_SNAPSHOTS exchange rates, keyed by id modulo its size
+pub const EXCHANGE_RATE_HISTORY: Map<U64Key, ExchangeRateSnapshot> =
+    Map::new("exchange_rate_history");
+pub const EXCHANGE_RATE_SNAPSHOTS_COUNT: Item<u64> = Item::new("exchange_rate_snapshots_count");
+pub const MAX_EXCHANGE_RATE_SNAPSHOTS: u64 = 1000;
+
+// Amounts the hub expects to be delegated to each validator, used to attribute slashing
+pub const DELEGATIONS_BOOK: Map<&str, Uint128> = Map::new("delegations_book");
+
+// Detected slashing shortfalls, keyed by id
+pub const SLASHING_EVENTS: Map<U64Key, SlashingEvent> = Map::new("slashing_events");
+pub const SLASHING_EVENTS_COUNT: Item<u64> = Item::new("slashing_events_count");
+
+// Timelocked owner's updates which are neither executed nor vetoed yet, keyed by id
+pub const PENDING_CHANGES: Map<U64Key, PendingChange> = Map::new("pending_changes");
+pub const PENDING_CHANGES_COUNT: Item<u64> = Item::new("pending_changes_count");
+
 pub static PREFIX_WAIT_MAP: &[u8] = b"wait";
 pub static UNBOND_HISTORY_MAP: &[u8] = b"history_map";
 
@@ -101,18 +129,43 @@ pub fn get_unbond_requests(storage: &dyn Storage, sender_addr: String) -> StdRes
 /// This needs to be called after process withdraw rate function.
 /// If the batch is released, this will return user's requested
 /// amount proportional to withdraw rate.
+/// Only the given batches are taken if batch_ids is set, and at most limit
+/// wait list entries are scanned if limit is set. Batches are released in order,
+/// so the released ones come first.
 pub fn get_finished_amount(
     storage: &dyn Storage,
     sender_addr: String,
+    batch_ids: Option<Vec<u64>>,
+    limit: Option<u32>,
 ) -> StdResult<(Uint128, Vec<u64>)> {
     let vec = to_vec(&sender_addr)?;
-    let mut withdrawable_amount: Uint128 = Uint128::zero();
-    let mut deprecated_batches: Vec<u64> = vec![];
     let res: ReadonlyBucket<UnbondWaitEntity> =
         ReadonlyBucket::multilevel(storage, &[PREFIX_WAIT_MAP, &vec]);
-    for item in res.range(None, None, Order::Ascending) {
-        let (k, v) = item?;
-        let user_batch: u64 = from_slice(&k)?;
+    let requests: Box<dyn Iterator<Item = StdResult<(u64, Option<UnbondWaitEntity>)>>> =
+        match batch_ids {
+            Some(mut ids) => {
+                ids.sort_unstable();
+                ids.dedup();
+                Box::new(
+                    ids.into_iter()
+                        .map(|id| Ok((id, res.may_load(&to_vec(&id)?)?))),
+                )
+            }
+            None => Box::new(res.range(None, None, Order::Ascending).map(|item| {
+                let (k, v) = item?;
+                Ok((from_slice(&k)?, Some(v)))
+            })),
+        };
+
+    let limit = limit.map(|l| l as usize).unwrap_or(usize::MAX);
+    let mut withdrawable_amount: Uint128 = Uint128::zero();
+    let mut deprecated_batches: Vec<u64> = vec![];
+    for item in requests.take(limit) {
+        let (user_batch, request) = item?;
+        let v = match request {
+            Some(v) => v,
+            None => continue,
+        };
         let history = read_unbond_history(storage, user_batch);
         if let Ok(h) = history {
             if h.released {
@@ -198,6 +251,190 @@ pub fn all_unbond_history(
     res
 }
 
+/// Append the exchange rate of the state to the history,
+/// overwriting the oldest snapshot once the history is full
+pub fn store_exchange_rate_snapshot(
+    storage: &mut dyn Storage,
+    env: &Env,
+    state: &State,
+    total_statom_issued: Uint128,
+) -> StdResult<()> {
+    let id = EXCHANGE_RATE_SNAPSHOTS_COUNT
+        .may_load(storage)?
+        .unwrap_or_default();
+    let snapshot = ExchangeRateSnapshot {
+        id,
+        time: env.block.time.seconds(),
+        height: env.block.height,
+        statom_exchange_rate: state.statom_exchange_rate,
+        total_bond_statom_amount: state.total_bond_statom_amount,
+        total_statom_issued,
+    };
+    EXCHANGE_RATE_HISTORY.save(
+        storage,
+        U64Key::new(id % MAX_EXCHANGE_RATE_SNAPSHOTS),
+        &snapshot,
+    )?;
+    EXCHANGE_RATE_SNAPSHOTS_COUNT.save(storage, &(id + 1))
+}
+
+/// Return the ids of the oldest and the newest snapshots kept in the history
+fn exchange_rate_snapshot_ids(storage: &dyn Storage) -> StdResult<Option<(u64, u64)>> {
+    let count = EXCHANGE_RATE_SNAPSHOTS_COUNT
+        .may_load(storage)?
+        .unwrap_or_default();
+    if count == 0 {
+        return Ok(None);
+    }
+    Ok(Some((
+        count.saturating_sub(MAX_EXCHANGE_RATE_SNAPSHOTS),
+        count - 1,
+    )))
+}
+
+fn read_exchange_rate_snapshot(storage: &dyn Storage, id: u64) -> StdResult<ExchangeRateSnapshot> {
+    EXCHANGE_RATE_HISTORY.load(storage, U64Key::new(id % MAX_EXCHANGE_RATE_SNAPSHOTS))
+}
+
+/// Return the kept exchange rate snapshots in ascending order of ids
+pub fn exchange_rate_history(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ExchangeRateSnapshot>> {
+    let (oldest, newest) = match exchange_rate_snapshot_ids(storage)? {
+        Some(ids) => ids,
+        None => return Ok(vec![]),
+    };
+    let start = start_after.map_or(oldest, |id| id.saturating_add(1).max(oldest));
+    let lim = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as u64;
+    if lim == 0 {
+        return Ok(vec![]);
+    }
+    let end = newest.min(start.saturating_add(lim - 1));
+    if start > end {
+        return Ok(vec![]);
+    }
+    (start..=end)
+        .map(|id| read_exchange_rate_snapshot(storage, id))
+        .collect()
+}
+
+/// Return the oldest kept snapshot taken at or after the given time and the newest snapshot
+pub fn exchange_rate_snapshots_since(
+    storage: &dyn Storage,
+    time: u64,
+) -> StdResult<Option<(ExchangeRateSnapshot, ExchangeRateSnapshot)>> {
+    let (oldest, newest) = match exchange_rate_snapshot_ids(storage)? {
+        Some(ids) => ids,
+        None => return Ok(None),
+    };
+    let last = read_exchange_rate_snapshot(storage, newest)?;
+    if last.time < time {
+        return Ok(None);
+    }
+
+    // snapshots are ordered by time, so the first one in the window is found by a binary search
+    let (mut low, mut high) = (oldest, newest);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if read_exchange_rate_snapshot(storage, mid)?.time < time {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(Some((read_exchange_rate_snapshot(storage, low)?, last)))
+}
+
+/// Account for coins delegated to the validator by the hub
+pub fn increase_book_delegation(
+    storage: &mut dyn Storage,
+    validator: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    DELEGATIONS_BOOK.update(storage, validator, |d| -> StdResult<Uint128> {
+        Ok(d.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Account for coins undelegated or redelegated from the validator by the hub
+pub fn decrease_book_delegation(
+    storage: &mut dyn Storage,
+    validator: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let remaining = DELEGATIONS_BOOK
+        .may_load(storage, validator)?
+        .unwrap_or_default()
+        .saturating_sub(amount);
+    if remaining.is_zero() {
+        DELEGATIONS_BOOK.remove(storage, validator);
+        Ok(())
+    } else {
+        DELEGATIONS_BOOK.save(storage, validator, &remaining)
+    }
+}
+
+/// Store the slashing event under the next id, which is returned
+pub fn store_slashing_event(storage: &mut dyn Storage, mut event: SlashingEvent) -> StdResult<u64> {
+    let id = SLASHING_EVENTS_COUNT.may_load(storage)?.unwrap_or_default();
+    event.id = id;
+    SLASHING_EVENTS.save(storage, U64Key::new(id), &event)?;
+    SLASHING_EVENTS_COUNT.save(storage, &(id + 1))?;
+    Ok(id)
+}
+
+/// Return the slashing events in ascending order of ids
+pub fn slashing_events(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<SlashingEvent>> {
+    let lim = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::new(id)));
+    SLASHING_EVENTS
+        .range(storage, start, None, Order::Ascending)
+        .take(lim)
+        .map(|item| item.map(|(_, event)| event))
+        .collect()
+}
+
+/// Load the parameters with the pause of the contracts lifted if it has expired
+pub fn load_parameters(storage: &dyn Storage, env: &Env) -> StdResult<Parameters> {
+    let mut params = PARAMETERS.load(storage)?;
+    params.lift_expired_pause(env.block.time.seconds());
+    Ok(params)
+}
+
+/// Store the pending change under the next id and return the id
+pub fn store_pending_change(
+    storage: &mut dyn Storage,
+    mut change: PendingChange,
+) -> StdResult<u64> {
+    let id = PENDING_CHANGES_COUNT.may_load(storage)?.unwrap_or_default();
+    change.id = id;
+    PENDING_CHANGES.save(storage, U64Key::new(id), &change)?;
+    PENDING_CHANGES_COUNT.save(storage, &(id + 1))?;
+    Ok(id)
+}
+
+/// Return the pending changes in ascending order of ids
+pub fn pending_changes(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PendingChange>> {
+    let lim = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::new(id)));
+    PENDING_CHANGES
+        .range(storage, start, None, Order::Ascending)
+        .take(lim)
+        .map(|item| item.map(|(_, change)| change))
+        .collect()
+}
+
 fn convert(start_after: Option<u64>) -> Option<Vec<u8>> {
     start_after.map(|idx| {
         let mut v = idx.to_be_bytes().to_vec();
@@ -91,7 +91,7 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr == VALIDATORS_REGISTRY {
                     let mut validators = self.validators.clone();
-                    validators.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+                    validators.sort_by_key(|v| v.total_delegated);
                     return SystemResult::Ok(ContractResult::from(to_binary(&validators)));
                 }
                 match from_binary(msg).unwrap() {
@@ -179,6 +179,7 @@ impl WasmMockQuerier {
                         statom_token_contract: Some(
                             api.addr_validate(&String::from("statom_token")).unwrap(),
                         ),
+                        claims_nft_contract: None,
                     };
                     QuerierResult::Ok(ContractResult::from(to_binary(
                         &to_binary(&config).unwrap(),
@@ -12,33 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! This integration test tries to run and call the generated wasm.
-//! It depends on a Wasm build being available, which you can create with `cargo wasm`.
-//! Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
-//!
-//! You can easily convert unit tests to integration tests as follows:
-//! 1. Copy them over verbatim
-//! 2. Then change
-//!      let mut deps = mock_dependencies &[]);
-//!    to
-//!      let mut deps = mock_instance(WASM, &[]);
-//! 3. If you access raw storage, where ever you see something like:
-//!      deps.storage.get(CONFIG_KEY).expect("no data stored");
-//!    replace it with:
-//!      deps.with_storage(|store| {
-//!          let data = store.get(CONFIG_KEY).expect("no data stored");
-//!          //...
-//!      });
-//! 4. Anywhere you see query(deps.as_ref(), ...) you must replace it with query(deps.as_mut(), ...)
+// This integration test tries to run and call the generated wasm.
+// It depends on a Wasm build being available, which you can create with `cargo wasm`.
+// Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
+//
+// You can easily convert unit tests to integration tests as follows:
+// 1. Copy them over verbatim
+// 2. Then change
+//      let mut deps = mock_dependencies &[]);
+//    to
+//      let mut deps = mock_instance(WASM, &[]);
+// 3. If you access raw storage, where ever you see something like:
+//      deps.storage.get(CONFIG_KEY).expect("no data stored");
+//    replace it with:
+//      deps.with_storage(|store| {
+//          let data = store.get(CONFIG_KEY).expect("no data stored");
+//          //...
+//      });
+// 4. Anywhere you see query(deps.as_ref(), ...) you must replace it with query(deps.as_mut(), ...)
 use cosmwasm_std::{
-    coin, coins, from_binary, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, DepsMut,
-    DistributionMsg, Env, FullDelegation, MessageInfo, OwnedDeps, Querier, QueryRequest, Response,
-    StakingMsg, StdError, Storage, Uint128, Validator, WasmMsg, WasmQuery,
+    attr, coin, coins, from_binary, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal,
+    DepsMut, DistributionMsg, Env, FullDelegation, MessageInfo, OwnedDeps, Querier, QueryRequest,
+    Response, StakingMsg, StdError, Storage, Uint128, Validator, WasmMsg, WasmQuery,
 };
 use lido_cosmos_validators_registry::msg::QueryMsg as QueryValidators;
-use lido_cosmos_validators_registry::registry::ValidatorResponse as RegistryValidator;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use lido_cosmos_validators_registry::registry::{
+    ValidatorResponse as RegistryValidator, DEFAULT_WEIGHT,
+};
 
 use cosmwasm_std::testing::{mock_env, mock_info};
 
@@ -52,16 +52,24 @@ use super::mock_querier::{mock_dependencies as dependencies, WasmMockQuerier};
 use crate::state::{read_unbond_wait_list, CONFIG};
 use lido_cosmos_rewards_dispatcher::msg::ExecuteMsg::DispatchRewards;
 
-use basset::hub::Cw20HookMsg::Unbond;
+use basset::access_control::{Role, RolesResponse};
+use basset::hub::Cw20HookMsg::{InstantUnbond, Unbond};
 use basset::hub::ExecuteMsg::{CheckSlashing, Receive, UpdateConfig, UpdateParams};
 use basset::hub::QueryMsg::{
     AllHistory, Config, CurrentBatch, Parameters as Params, State, UnbondRequests,
     WithdrawableUnbonded,
 };
 use basset::hub::{
-    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, ExecuteMsg, InstantiateMsg,
-    Parameters, QueryMsg, StateResponse, UnbondRequestsResponse, WithdrawableUnbondedResponse,
+    AllHistoryResponse, AprResponse, ClaimMintMsg, ClaimsNftExecuteMsg, ConfigResponse,
+    CurrentBatchResponse, Cw721HookMsg, Cw721ReceiveMsg, ExchangeRateHistoryResponse,
+    ExchangeRateSnapshot, ExecuteMsg, InstantiateMsg, OperationPause, Parameters,
+    PausableOperation, PauseInfo, PauseStateResponse, PendingChange, PendingChangeMsg,
+    PendingChangesResponse, QueryMsg, SlashingEvent, SlashingEventsResponse, StateResponse,
+    UnbondClaim, UnbondClaimResponse, UnbondRequestDetails, UnbondRequestStatus,
+    UnbondRequestsDetailedResponse, UnbondRequestsResponse, ValidatorSlashing,
+    WithdrawableUnbondedResponse,
 };
+use basset::ownership::PendingOwnerResponse;
 use cosmwasm_std::testing::{MockApi, MockStorage};
 use std::borrow::BorrowMut;
 
@@ -104,15 +112,16 @@ pub fn initialize<S: Storage, A: Api, Q: Querier>(
         epoch_period: 30,
         underlying_coin_denom: "uatom".to_string(),
         unbonding_period: 2,
+        min_total_supply: Uint128::zero(),
     };
 
     let owner_info = mock_info(owner.as_str(), &[]);
     instantiate(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
 
     let register_msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         rewards_dispatcher_contract: Some(reward_contract),
         statom_token_contract: Some(statom_token_contract),
+        claims_nft_contract: None,
         validators_registry_contract: Some(String::from("validators_registry")),
     };
     let res = execute(deps.as_mut(), mock_env(), owner_info, register_msg).unwrap();
@@ -126,6 +135,8 @@ pub fn do_register_validator(
     deps.querier.add_validator(RegistryValidator {
         total_delegated: Default::default(),
         address: validator.address,
+        weight: DEFAULT_WEIGHT,
+        max_delegation: None,
     });
 }
 
@@ -156,7 +167,10 @@ pub fn do_unbond(
     info: MessageInfo,
     amount: Uint128,
 ) -> Response {
-    let successful_bond = Unbond {};
+    let successful_bond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: addr,
         amount,
@@ -181,6 +195,7 @@ fn proper_initialization() {
         epoch_period: 30,
         underlying_coin_denom: "uatom".to_string(),
         unbonding_period: 210,
+        min_total_supply: Uint128::zero(),
     };
 
     let owner = String::from("owner1");
@@ -209,6 +224,10 @@ fn proper_initialization() {
         prev_hub_balance: Default::default(),
         last_unbonded_time: env.block.time.seconds(),
         last_processed_batch: 0u64,
+        liquidity_buffer: Uint128::zero(),
+        insurance_reserve: Uint128::zero(),
+        deferred_rewards: Uint128::zero(),
+        unbonding_amount: Uint128::zero(),
     };
     assert_eq!(query_state, expected_result);
 
@@ -221,6 +240,7 @@ fn proper_initialization() {
         reward_dispatcher_contract: None,
         validators_registry_contract: None,
         statom_token_contract: None,
+        claims_nft_contract: None,
     };
 
     assert_eq!(expected_conf, query_conf);
@@ -595,7 +615,13 @@ pub fn proper_update_global_index() {
             funds: _,
         }) => {
             assert_eq!(contract_addr, reward_contract);
-            assert_eq!(msg, to_binary(&DispatchRewards {}).unwrap())
+            assert_eq!(
+                msg,
+                to_binary(&DispatchRewards {
+                    validator_rewards: vec![]
+                })
+                .unwrap()
+            )
         }
         _ => panic!("Unexpected message: {:?}", update_g_index),
     }
@@ -789,7 +815,10 @@ pub fn proper_receive_statom() {
     assert!(res.is_err());
 
     // unauthorized
-    let failed_unbond = Unbond {};
+    let failed_unbond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: addr1.clone(),
         amount: Uint128::from(10u64),
@@ -801,7 +830,10 @@ pub fn proper_receive_statom() {
     assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
 
     // successful call
-    let successful_unbond = Unbond {};
+    let successful_unbond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: addr1,
         amount: Uint128::from(10u64),
@@ -899,7 +931,10 @@ pub fn proper_unbond_statom() {
     assert_eq!(query_state.total_bond_statom_amount, Uint128::from(10u64));
 
     // successful call
-    let successful_bond = Unbond {};
+    let successful_bond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: bob.clone(),
         amount: Uint128::from(1u64),
@@ -915,7 +950,10 @@ pub fn proper_unbond_statom() {
     assert_eq!(Uint128::from(1u64), wait_list.statom_amount);
 
     // successful call
-    let successful_bond = Unbond {};
+    let successful_bond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: bob.clone(),
         amount: Uint128::from(5u64),
@@ -959,7 +997,10 @@ pub fn proper_unbond_statom() {
     // pushing time forward to check the unbond message
     env.block.time = env.block.time.plus_seconds(31);
 
-    let successful_bond = Unbond {};
+    let successful_bond = Unbond {
+        recipient: None,
+        mint_claim: false,
+    };
     let receive = Receive(Cw20ReceiveMsg {
         sender: bob.clone(),
         amount: Uint128::from(2u64),
@@ -1032,19 +1073,11 @@ pub fn proper_unbond_statom() {
 
 /// Covers if the undelegate function sends different Undelegate messages
 /// to different validators, when a validator does not have enough delegation.
+/// Covers the status, the expected amount and the release time of each unbond request.
 #[test]
-pub fn proper_undelegate() {
+pub fn proper_unbond_requests_detailed() {
     let mut deps = dependencies(&[]);
-
-    let addr1 = String::from("addr1000");
-    let addr2 = String::from("addr2000");
-    let addr3 = String::from("addr3000");
-
-    // create 3 validators
     let validator = sample_validator(DEFAULT_VALIDATOR);
-    let validator2 = sample_validator(DEFAULT_VALIDATOR2);
-    let validator3 = sample_validator(DEFAULT_VALIDATOR3);
-
     set_validator_mock(&mut deps.querier);
 
     let owner = String::from("owner1");
@@ -1057,115 +1090,100 @@ pub fn proper_undelegate() {
         reward_contract,
         statom_token_contract.clone(),
     );
-
     do_register_validator(&mut deps, validator.clone());
-    do_register_validator(&mut deps, validator2.clone());
-    do_register_validator(&mut deps, validator3.clone());
 
-    // bond to a validator
-    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(10u64));
-    do_bond_statom(&mut deps, addr2.clone(), Uint128::from(150u64));
-    do_bond_statom(&mut deps, addr3.clone(), Uint128::from(200u64));
+    let bob = String::from("bob");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    set_delegation(&mut deps.querier, validator, 100, "uatom");
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(100u128))])]);
 
-    // give validators different delegation amount
-    let delegations: [FullDelegation; 3] = [
-        (sample_delegation(validator.address.clone(), coin(10, "uatom"))),
-        (sample_delegation(validator2.address.clone(), coin(150, "uatom"))),
-        (sample_delegation(validator3.address.clone(), coin(200, "uatom"))),
-    ];
+    let start = mock_env().block.time.seconds();
+    execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        Uint128::from(10u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(90u128))])]);
 
-    let validators: [Validator; 3] = [(validator), (validator2.clone()), (validator3.clone())];
-    set_delegation_query(&mut deps.querier, &delegations, &validators);
-    deps.querier.with_token_balances(&[(
-        &statom_token_contract,
-        &[
-            (&addr3, &Uint128::from(200u64)),
-            (&addr2, &Uint128::from(150u64)),
-            (&addr1, &Uint128::from(10u64)),
-        ],
-    )]);
+    let detailed = QueryMsg::UnbondRequestsDetailed {
+        address: bob.clone(),
+    };
+    let res: UnbondRequestsDetailedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), detailed.clone()).unwrap()).unwrap();
+    assert_eq!(
+        res.requests,
+        vec![UnbondRequestDetails {
+            batch_id: 1,
+            statom_amount: Uint128::from(10u64),
+            status: UnbondRequestStatus::Pending,
+            expected_amount: Uint128::from(10u64),
+            release_time: start + 31 + 2,
+        }]
+    );
 
-    // send the first burn
-    let token_info = mock_info(&statom_token_contract, &[]);
+    // the first batch is undelegated before the second request
     let mut env = mock_env();
-    let res = do_unbond(
+    env.block.time = env.block.time.plus_seconds(31);
+    execute(
         deps.as_mut(),
-        addr2.clone(),
         env.clone(),
-        token_info.clone(),
-        Uint128::from(50u64),
-    );
-    assert_eq!(res.messages.len(), 1);
-
-    deps.querier.with_token_balances(&[(
-        &statom_token_contract,
-        &[
-            (&addr3, &Uint128::from(200u64)),
-            (&addr2, &Uint128::from(100u64)),
-            (&addr1, &Uint128::from(10u64)),
-        ],
-    )]);
-
-    env.block.time = env.block.time.plus_seconds(40);
-
-    // send the second burn
-    let res = do_unbond(
+        mock_info(&bob, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+    execute_unbond_statom(
         deps.as_mut(),
-        addr2.clone(),
-        env,
-        token_info,
-        Uint128::from(100u64),
-    );
-
-    assert_eq!(res.messages.len(), 3);
+        env.clone(),
+        Uint128::from(20u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(70u128))])]);
 
-    deps.querier.with_token_balances(&[(
-        &statom_token_contract,
-        &[
-            (&addr3, &Uint128::from(200u64)),
-            (&addr2, &Uint128::from(0u64)),
-            (&addr1, &Uint128::from(10u64)),
-        ],
-    )]);
+    let res: UnbondRequestsDetailedResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), detailed.clone()).unwrap()).unwrap();
+    assert_eq!(
+        res.requests,
+        vec![
+            UnbondRequestDetails {
+                batch_id: 1,
+                statom_amount: Uint128::from(10u64),
+                status: UnbondRequestStatus::Unbonding,
+                expected_amount: Uint128::from(10u64),
+                release_time: start + 31 + 2,
+            },
+            UnbondRequestDetails {
+                batch_id: 2,
+                statom_amount: Uint128::from(20u64),
+                status: UnbondRequestStatus::Pending,
+                expected_amount: Uint128::from(20u64),
+                release_time: start + 62 + 2,
+            },
+        ]
+    );
 
-    // check if the undelegate message is send two more than one validator.
-    match &res.messages[0].msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Undelegate {
-            validator: val,
-            amount,
-        }) => {
-            assert_eq!(val, &validator3.address);
-            assert_eq!(amount.amount, Uint128::from(130u64));
-        }
-        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
-    }
-    match &res.messages[1].msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Undelegate {
-            validator: val,
-            amount,
-        }) => {
-            assert_eq!(val, &validator2.address);
-            assert_eq!(amount.amount, Uint128::from(20u64));
-        }
-        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
-    }
+    env.block.time = env.block.time.plus_seconds(2);
+    let res: UnbondRequestsDetailedResponse =
+        from_binary(&query(deps.as_ref(), env, detailed).unwrap()).unwrap();
+    assert_eq!(res.requests[0].status, UnbondRequestStatus::Withdrawable);
+    assert_eq!(res.requests[1].status, UnbondRequestStatus::Pending);
 }
 
-/// Covers if the undelegate function sends different Undelegate messages
-/// if the delegations of the user are distributed to several validators
-/// and if the user wants to unbond amount that none of validators has.
+/// Covers recording the exchange rate changed by bonded rewards and slashing,
+/// and the APR computed from the recorded rates.
 #[test]
-pub fn proper_undelegate_respect_distributed_delegation() {
+pub fn proper_exchange_rate_history_and_apr() {
     let mut deps = dependencies(&[]);
-
-    let addr1 = String::from("addr1000");
-    let addr2 = String::from("addr2000");
-
-    // create 3 validators
     let validator = sample_validator(DEFAULT_VALIDATOR);
-    let validator2 = sample_validator(DEFAULT_VALIDATOR2);
-    let validator3 = sample_validator(DEFAULT_VALIDATOR3);
-
     set_validator_mock(&mut deps.querier);
 
     let owner = String::from("owner1");
@@ -1175,605 +1193,862 @@ pub fn proper_undelegate_respect_distributed_delegation() {
     initialize(
         deps.borrow_mut(),
         owner,
-        reward_contract,
+        reward_contract.clone(),
         statom_token_contract.clone(),
     );
-
     do_register_validator(&mut deps, validator.clone());
-    do_register_validator(&mut deps, validator2.clone());
-    do_register_validator(&mut deps, validator3);
 
-    // bond to a validator
-    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1000u64));
-    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1500u64));
+    let bob = String::from("bob");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    set_delegation(&mut deps.querier, validator.clone(), 100, "uatom");
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(100u128))])]);
 
-    // give validators different delegation amount
-    let delegations: [FullDelegation; 2] = [
-        (sample_delegation(validator.address.clone(), coin(1000, "uatom"))),
-        (sample_delegation(validator2.address.clone(), coin(1500, "uatom"))),
-    ];
+    let history = QueryMsg::ExchangeRateHistory {
+        start_after: None,
+        limit: None,
+    };
+    let res: ExchangeRateHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), history.clone()).unwrap()).unwrap();
+    assert!(res.history.is_empty());
 
-    let validators: [Validator; 2] = [(validator), (validator2)];
-    set_delegation_query(&mut deps.querier, &delegations, &validators);
+    // the rewards of a year raise the rate from 1.1 to 1.21
+    let start = mock_env();
+    execute(
+        deps.as_mut(),
+        start.clone(),
+        mock_info(&reward_contract, &[coin(10, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    set_delegation(&mut deps.querier, validator.clone(), 110, "uatom");
 
-    deps.querier.with_token_balances(&[
-        (&String::from("token"), &[(&addr1, &Uint128::from(2500u64))]),
-        (&statom_token_contract, &[]),
-    ]);
+    let mut end = mock_env();
+    end.block.time = end.block.time.plus_seconds(365 * 24 * 60 * 60);
+    end.block.height += 1000;
+    execute(
+        deps.as_mut(),
+        end.clone(),
+        mock_info(&reward_contract, &[coin(11, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    set_delegation(&mut deps.querier, validator.clone(), 121, "uatom");
 
-    // send the first burn
-    let token_info = mock_info(&statom_token_contract, &[]);
-    let mut env = mock_env();
+    let res: ExchangeRateHistoryResponse =
+        from_binary(&query(deps.as_ref(), end.clone(), history.clone()).unwrap()).unwrap();
+    assert_eq!(
+        res.history,
+        vec![
+            ExchangeRateSnapshot {
+                id: 0,
+                time: start.block.time.seconds(),
+                height: start.block.height,
+                statom_exchange_rate: Decimal::from_ratio(11u128, 10u128),
+                total_bond_statom_amount: Uint128::from(110u64),
+                total_statom_issued: Uint128::from(100u64),
+            },
+            ExchangeRateSnapshot {
+                id: 1,
+                time: end.block.time.seconds(),
+                height: end.block.height,
+                statom_exchange_rate: Decimal::from_ratio(121u128, 100u128),
+                total_bond_statom_amount: Uint128::from(121u64),
+                total_statom_issued: Uint128::from(100u64),
+            },
+        ]
+    );
 
-    env.block.time = env.block.time.plus_seconds(40);
+    let page = QueryMsg::ExchangeRateHistory {
+        start_after: Some(0),
+        limit: Some(1),
+    };
+    let res: ExchangeRateHistoryResponse =
+        from_binary(&query(deps.as_ref(), end.clone(), page).unwrap()).unwrap();
+    assert_eq!(res.history.len(), 1);
+    assert_eq!(res.history[0].id, 1);
+
+    let empty_page = QueryMsg::ExchangeRateHistory {
+        start_after: None,
+        limit: Some(0),
+    };
+    let res: ExchangeRateHistoryResponse =
+        from_binary(&query(deps.as_ref(), end.clone(), empty_page).unwrap()).unwrap();
+    assert!(res.history.is_empty());
+
+    let res: AprResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            end.clone(),
+            QueryMsg::Apr {
+                window_seconds: 2 * 365 * 24 * 60 * 60,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.apr, Some(Decimal::percent(10)));
+    assert_eq!(res.from_time, Some(start.block.time.seconds()));
+    assert_eq!(res.to_time, Some(end.block.time.seconds()));
+
+    // a single snapshot in the window is not enough
+    let res: AprResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            end.clone(),
+            QueryMsg::Apr { window_seconds: 10 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.apr, None);
 
-    let res = do_unbond(
+    // slashing is recorded as well
+    set_delegation(&mut deps.querier, validator, 100, "uatom");
+    execute(
         deps.as_mut(),
-        addr2,
-        env,
-        token_info,
-        Uint128::from(2000u64),
-    );
-    assert_eq!(res.messages.len(), 3);
-
-    match &res.messages[0].msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Undelegate {
-            validator: _,
-            amount,
-        }) => assert_eq!(amount.amount, Uint128::from(1250u64)),
-        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
-    }
+        end.clone(),
+        mock_info(&bob, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    let res: ExchangeRateHistoryResponse =
+        from_binary(&query(deps.as_ref(), end.clone(), history).unwrap()).unwrap();
+    assert_eq!(res.history.len(), 3);
+    assert_eq!(res.history[2].statom_exchange_rate, Decimal::one());
+
+    let res: AprResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            end,
+            QueryMsg::Apr {
+                window_seconds: 2 * 365 * 24 * 60 * 60,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.apr, Some(Decimal::zero()));
 }
 
-/// Covers the effect of slashing of bond, unbond, and withdraw_unbonded
-/// update the exchange rate after and before slashing.
+/// Covers recording slashing events with the shortfall of each validator.
 #[test]
-pub fn proper_slashing_statom() {
+pub fn proper_slashing_events() {
     let mut deps = dependencies(&[]);
     let validator = sample_validator(DEFAULT_VALIDATOR);
+    let second_validator = sample_validator("second_validator");
     set_validator_mock(&mut deps.querier);
 
     let addr1 = String::from("addr1000");
-
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
     initialize(
         deps.borrow_mut(),
-        owner,
+        owner.clone(),
         reward_contract,
         statom_token_contract.clone(),
     );
-
-    // register_validator
     do_register_validator(&mut deps, validator.clone());
-
-    // bond
     do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1000u64));
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[(&addr1, &Uint128::from(1000u128))],
+    )]);
 
-    // this will set the balance of the user in token contract
-    deps.querier.with_token_balances(&[
-        (
-            &statom_token_contract,
-            &[(&addr1, &Uint128::from(1000u128))],
-        ),
-        (&String::from("token"), &[]),
-    ]);
-
-    // slashing
-    set_delegation(&mut deps.querier, validator.clone(), 900, "uatom");
-
-    let info = mock_info(&addr1, &[]);
-    let report_slashing = CheckSlashing {};
-    let res = execute(deps.as_mut(), mock_env(), info, report_slashing).unwrap();
-    assert_eq!(0, res.messages.len());
+    // move a part of the delegation to the second validator
+    let redelegate_proxy_msg = ExecuteMsg::RedelegateProxy {
+        src_validator: DEFAULT_VALIDATOR.to_string(),
+        redelegations: vec![(second_validator.address.clone(), coin(400, "uatom"))],
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        redelegate_proxy_msg,
+    )
+    .unwrap();
 
-    let ex_rate = State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(query_exchange_rate.statom_exchange_rate.to_string(), "0.9");
+    // the second validator is slashed
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(validator.address.clone(), coin(600, "uatom")),
+            sample_delegation(second_validator.address.clone(), coin(300, "uatom")),
+        ],
+        &[validator.clone(), second_validator.clone()],
+    );
+    let env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
 
-    // bond again to see the update exchange rate
-    let second_bond = ExecuteMsg::BondForStAtom {};
+    let first_event = SlashingEvent {
+        id: 0,
+        time: env.block.time.seconds(),
+        height: env.block.height,
+        slashed_amount: Uint128::from(100u64),
+        covered_amount: Uint128::zero(),
+        validators: vec![ValidatorSlashing {
+            validator: second_validator.address.clone(),
+            expected_delegation: Uint128::from(400u64),
+            actual_delegation: Uint128::from(300u64),
+        }],
+        statom_exchange_rate_before: Decimal::one(),
+        statom_exchange_rate_after: Decimal::percent(90),
+    };
+    let res: SlashingEvent =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SlashingEvent { id: 0 }).unwrap())
+            .unwrap();
+    assert_eq!(res, first_event);
 
-    let info = mock_info(&addr1, &[coin(900, "uatom")]);
+    // no new event while the delegations stay the same
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), second_bond).unwrap();
-    assert_eq!(2, res.messages.len());
-
-    let expected_er = "0.9";
-    let ex_rate = State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(
-        query_exchange_rate.statom_exchange_rate.to_string(),
-        expected_er
+    // then the first validator is slashed, the earlier shortfall is not counted again
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(validator.address.clone(), coin(510, "uatom")),
+            sample_delegation(second_validator.address.clone(), coin(300, "uatom")),
+        ],
+        &[validator.clone(), second_validator],
     );
-
-    let delegate = &res.messages[0];
-    match delegate.msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
-            assert_eq!(amount, coin(900, "uatom"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
-
-    let message = &res.messages[1];
-    match message.msg.clone() {
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr,
-            msg,
-            funds: _,
-        }) => {
-            assert_eq!(contract_addr, statom_token_contract);
-            assert_eq!(
-                msg,
-                to_binary(&Mint {
-                    recipient: info.sender.to_string(),
-                    amount: Uint128::from(1000u64)
-                })
-                .unwrap()
-            );
-        }
-        _ => panic!("Unexpected message: {:?}", message),
-    }
-
-    set_delegation(&mut deps.querier, validator.clone(), 1800, "uatom");
-
-    // update user balance
-    deps.querier.with_token_balances(&[
-        (
-            &statom_token_contract,
-            &[(&addr1, &Uint128::from(2000u128))],
-        ),
-        (&String::from("token"), &[]),
-    ]);
-
-    let info = mock_info(&addr1, &[]);
-    let mut env = mock_env();
-    let _res = execute_unbond_statom(
-        deps.as_mut(),
-        env.clone(),
-        Uint128::from(500u64),
-        addr1.clone(),
-    )
-    .unwrap();
-
-    deps.querier.with_token_balances(&[
-        (
-            &statom_token_contract,
-            &[(&addr1, &Uint128::from(1500u128))],
-        ),
-        (&String::from("token"), &[]),
-    ]);
-
-    env.block.time = env.block.time.plus_seconds(31);
-    let res = execute_unbond_statom(
+    execute(
         deps.as_mut(),
-        env.clone(),
-        Uint128::from(500u64),
-        addr1.clone(),
+        mock_env(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
     )
     .unwrap();
-    let msgs: CosmosMsg = CosmosMsg::Staking(StakingMsg::Undelegate {
-        validator: validator.address,
-        amount: coin(900, "uatom"),
-    });
-    assert_eq!(res.messages[0].msg, msgs);
-
-    deps.querier.with_token_balances(&[(
-        &statom_token_contract,
-        &[(&addr1, &Uint128::from(1000u128))],
-    )]);
-
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(900u64),
-        },
-    )]);
 
-    let ex_rate = State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    let all_events = QueryMsg::SlashingEvents {
+        start_after: None,
+        limit: None,
+    };
+    let res: SlashingEventsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_events).unwrap()).unwrap();
+    assert_eq!(res.events.len(), 2);
+    assert_eq!(res.events[0], first_event);
+    assert_eq!(res.events[1].id, 1);
+    assert_eq!(res.events[1].slashed_amount, Uint128::from(90u64));
     assert_eq!(
-        query_exchange_rate.statom_exchange_rate.to_string(),
-        expected_er
+        res.events[1].validators,
+        vec![ValidatorSlashing {
+            validator: validator.address,
+            expected_delegation: Uint128::from(600u64),
+            actual_delegation: Uint128::from(510u64),
+        }]
     );
-
-    env.block.time = env.block.time.plus_seconds(90);
-    // check withdrawUnbonded message
-    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(deps.as_mut(), env, info, withdraw_unbond_msg).unwrap();
-    assert_eq!(wdraw_unbonded_res.messages.len(), 1);
-
-    let ex_rate = State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
     assert_eq!(
-        query_exchange_rate.statom_exchange_rate.to_string(),
-        expected_er
+        res.events[1].statom_exchange_rate_after,
+        Decimal::percent(81)
     );
 
-    let sent_message = &wdraw_unbonded_res.messages[0];
-    match sent_message.msg.clone() {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, addr1);
-            assert_eq!(amount[0].amount, Uint128::from(900u64))
-        }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    let next_events = QueryMsg::SlashingEvents {
+        start_after: Some(0),
+        limit: Some(10),
+    };
+    let res: SlashingEventsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), next_events).unwrap()).unwrap();
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].id, 1);
 }
 
-/// Covers if the withdraw_rate function is updated before and after withdraw_unbonded,
-/// the finished amount is accurate, user requests are removed from the waitlist, and
-/// the BankMsg::Send is sent.
+/// Covers funding the insurance reserve and delegating it back on slashing up to the cap.
 #[test]
-pub fn proper_withdraw_unbonded_statom() {
+pub fn proper_insurance_cover() {
     let mut deps = dependencies(&[]);
-
     let validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
+    let addr1 = String::from("addr1000");
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
-
     initialize(
         deps.borrow_mut(),
-        owner,
+        owner.clone(),
         reward_contract,
         statom_token_contract.clone(),
     );
-
-    // register_validator
     do_register_validator(&mut deps, validator.clone());
+    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1000u64));
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[(&addr1, &Uint128::from(1000u128))],
+    )]);
 
-    let bob = String::from("bob");
-    let bond_msg = ExecuteMsg::BondForStAtom {};
-
-    let info = mock_info(&bob, &[coin(100, "uatom")]);
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: Some(Uint128::from(50u64)),
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_params,
+    )
+    .unwrap();
 
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
+    // only the underlying can fund the reserve
+    let funder = String::from("funder");
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&funder, &[coin(80, "ukrw")]),
+        ExecuteMsg::FundInsurance {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("No uatom assets are provided to fund")
+    );
 
-    // set bob's balance to 10 in token contract
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(100u128))]),
-        (&String::from("token"), &[]),
-    ]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&funder, &[coin(80, "uatom")]),
+        ExecuteMsg::FundInsurance {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fund_insurance"),
+            attr("from", &funder),
+            attr("amount", "80"),
+            attr("insurance_reserve", "80"),
+        ]
+    );
 
-    let delegate = &res.messages[0];
-    match delegate.msg.clone() {
+    // the cap limits the cover of a large slashing, the rest is socialized
+    set_delegation(&mut deps.querier, validator.clone(), 900, "uatom");
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    match res.messages[0].msg.clone() {
         CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
             assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
-            assert_eq!(amount, coin(100, "uatom"));
+            assert_eq!(amount, coin(50, "uatom"));
         }
-        _ => panic!("Unexpected message: {:?}", delegate),
+        _ => panic!("Unexpected message: {:?}", res.messages[0]),
     }
+    set_delegation(&mut deps.querier, validator.clone(), 950, "uatom");
 
-    let bond_msg = ExecuteMsg::BondRewards {};
-
-    let info = mock_info(&String::from("reward"), &[coin(100, "uatom")]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(1, res.messages.len());
-
-    set_delegation(&mut deps.querier, validator, 200, "uatom");
-
-    let res = execute_unbond_statom(deps.as_mut(), mock_env(), Uint128::from(10u64), bob.clone())
-        .unwrap();
-    assert_eq!(1, res.messages.len());
-
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(90u128))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(0u64),
-        },
-    )]);
-
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-    // set the block time 30 seconds from now.
-    env.block.time = env.block.time.plus_seconds(31);
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(state.statom_exchange_rate, Decimal::percent(95));
+    assert_eq!(state.total_bond_statom_amount, Uint128::from(950u64));
+    assert_eq!(state.insurance_reserve, Uint128::from(30u64));
+    assert_eq!(state.prev_hub_balance, Uint128::from(30u64));
 
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
+    // a small slashing is covered in full and the rate stays the same
+    set_delegation(&mut deps.querier, validator.clone(), 940, "uatom");
+    let res = execute(
         deps.as_mut(),
-        env.clone(),
-        info.clone(),
-        wdraw_unbonded_msg.clone(),
+        mock_env(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    set_delegation(&mut deps.querier, validator, 950, "uatom");
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(state.statom_exchange_rate, Decimal::percent(95));
+    assert_eq!(state.insurance_reserve, Uint128::from(20u64));
+
+    let events: SlashingEventsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SlashingEvents {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(events.events.len(), 2);
+    assert_eq!(events.events[0].slashed_amount, Uint128::from(100u64));
+    assert_eq!(events.events[0].covered_amount, Uint128::from(50u64));
+    assert_eq!(events.events[1].slashed_amount, Uint128::from(10u64));
+    assert_eq!(events.events[1].covered_amount, Uint128::from(10u64));
+    assert_eq!(
+        events.events[1].statom_exchange_rate_after,
+        events.events[1].statom_exchange_rate_before
     );
+}
 
-    // trigger undelegation message
-    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
-    assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uatom assets are available yet")
+/// Covers the first depositor inflating the exchange rate with a donation,
+/// without and with the minimum bond, the dead shares and the rate increase bound.
+#[test]
+pub fn proper_first_depositor_inflation() {
+    let attacker = String::from("attacker");
+    let victim = String::from("victim");
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+
+    // unprotected: a donation to a tiny supply lets the victim's bond round down to 1 stAtom
+    let mut deps = dependencies(&[]);
+    set_validator_mock(&mut deps.querier);
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract.clone(),
+        statom_token_contract.clone(),
     );
+    do_register_validator(&mut deps, validator.clone());
+    do_bond_statom(&mut deps, attacker.clone(), Uint128::from(1u64));
+    set_delegation(&mut deps.querier, validator.clone(), 1, "uatom");
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[(&attacker, &Uint128::from(1u128))],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&reward_contract, &[coin(10000, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    set_delegation(&mut deps.querier, validator.clone(), 10001, "uatom");
 
-    let res = execute_unbond_statom(
+    let res = execute(
         deps.as_mut(),
-        env.clone(),
-        Uint128::from(10u64),
-        bob.clone(),
+        mock_env(),
+        mock_info(&victim, &[coin(20000, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
     )
     .unwrap();
-    assert_eq!(res.messages.len(), 2);
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(80u128))]),
-        (&String::from("token"), &[]),
-    ]);
+    assert!(res.attributes.contains(&attr("minted", "1")));
 
-    let state = State {};
-    let query_state: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
-    assert_eq!(query_state.total_bond_statom_amount, Uint128::from(160u64));
+    // protected
+    let mut deps = dependencies(&[]);
+    set_validator_mock(&mut deps.querier);
+    let owner_info = mock_info(&owner, &[]);
+    let msg = InstantiateMsg {
+        epoch_period: 30,
+        underlying_coin_denom: "uatom".to_string(),
+        unbonding_period: 2,
+        min_total_supply: Uint128::from(1000u64),
+    };
+    instantiate(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
+    let register_msg = ExecuteMsg::UpdateConfig {
+        rewards_dispatcher_contract: Some(reward_contract.clone()),
+        statom_token_contract: Some(statom_token_contract.clone()),
+        claims_nft_contract: None,
+        validators_registry_contract: Some(String::from("validators_registry")),
+    };
+    execute(deps.as_mut(), mock_env(), owner_info.clone(), register_msg).unwrap();
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: Some(Uint128::from(1000u64)),
+        max_exchange_rate_increase: Some(Decimal::percent(10)),
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    execute(deps.as_mut(), mock_env(), owner_info, update_params).unwrap();
+    do_register_validator(&mut deps, validator.clone());
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[])]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&attacker, &[coin(1, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap_err();
     assert_eq!(
-        query_state.statom_exchange_rate,
-        Decimal::from_ratio(2u128, 1u128)
+        res,
+        StdError::generic_err("Bond amount must be at least 1000uatom")
     );
 
-    // this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::from(0u64));
-
-    env.block.time = env.block.time.plus_seconds(91);
+    // nobody but the owner can bond before the dead shares are minted
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&attacker, &[coin(1001, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(
+            "Bonding is disabled until the owner has bonded the minimum supply of 1000 stAtom"
+        )
+    );
 
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(40u64),
-        },
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[coin(1000, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: statom_token_contract.clone(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(1000u64),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert!(res.attributes.contains(&attr("minted", "0")));
+    assert!(res.attributes.contains(&attr("dead_shares", "1000")));
+    set_delegation(&mut deps.querier, validator.clone(), 1000, "uatom");
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
     )]);
-    // first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-    };
-    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    // the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::from(20u64));
-    assert_eq!(res.requests[0].0, 1);
-
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].statom_amount, Uint128::from(20u64));
-    assert_eq!(res.history[0].batch_id, 1);
-
-    // check with query
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::from(40u64));
 
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&attacker, &[coin(1000, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("minted", "1000")));
+    set_delegation(&mut deps.querier, validator.clone(), 2000, "uatom");
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[
+            (&attacker, &Uint128::from(1000u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128)),
+        ],
+    )]);
 
-    assert_eq!(success_res.messages.len(), 1);
+    // the donation raises the rate by the bound at most, the rest is deferred
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&reward_contract, &[coin(10000, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("bonded", "200")));
+    assert!(res.attributes.contains(&attr("deferred", "9800")));
+    set_delegation(&mut deps.querier, validator.clone(), 2200, "uatom");
 
-    let sent_message = &success_res.messages[0];
-    match sent_message.msg.clone() {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, bob);
-            assert_eq!(amount[0].amount, Uint128::from(40u64))
-        }
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(
+        state.statom_exchange_rate,
+        Decimal::from_ratio(11u128, 10u128)
+    );
+    assert_eq!(state.deferred_rewards, Uint128::from(9800u64));
+    assert_eq!(state.prev_hub_balance, Uint128::from(9800u64));
 
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    // the victim gets a fair amount of stAtom
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&victim, &[coin(20000, "uatom")]),
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("minted", "18181")));
+    set_delegation(&mut deps.querier, validator, 22200, "uatom");
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[
+            (&attacker, &Uint128::from(1000u128)),
+            (&victim, &Uint128::from(18181u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128)),
+        ],
+    )]);
 
-    // it should be removed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+    // the deferred rewards are bonded by the next ones
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&reward_contract, &[coin(100, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("bonded", "2219")));
+    assert!(res.attributes.contains(&attr("deferred", "7681")));
 
-    let waitlist = UnbondRequests {
-        address: bob.clone(),
+    // a zero bound clears it, the deferred rewards are bonded at once
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: Some(Decimal::zero()),
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
     };
-    let query_unbond: UnbondRequestsResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), waitlist).unwrap()).unwrap();
-    assert_eq!(
-        query_unbond,
-        UnbondRequestsResponse {
-            address: bob,
-            requests: vec![]
-        }
-    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_params,
+    )
+    .unwrap();
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.max_exchange_rate_increase, None);
 
-    // because of one that we add for each batch
-    let state = State {};
-    let state_query: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
-    assert_eq!(state_query.prev_hub_balance, Uint128::from(0u64));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&reward_contract, &[coin(1, "uatom")]),
+        ExecuteMsg::BondRewards {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("bonded", "7682")));
+    assert!(res.attributes.contains(&attr("deferred", "0")));
 }
 
-/// Covers slashing during the unbonded period and its effect on the finished amount.
+/// Covers if the owner's updates wait for the timelock period and can be vetoed by guardians.
 #[test]
-pub fn proper_withdraw_unbonded_respect_slashing_statom() {
+pub fn proper_timelocked_changes() {
     let mut deps = dependencies(&[]);
 
-    let validator = sample_validator(DEFAULT_VALIDATOR);
+    let _validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
-    let bond_amount = Uint128::from(10000u64);
-    let unbond_amount = Uint128::from(500u64);
-
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
+    let guardian = String::from("guardian");
 
     initialize(
         deps.borrow_mut(),
-        owner,
+        owner.clone(),
         reward_contract,
-        statom_token_contract.clone(),
+        statom_token_contract,
     );
 
-    // register_validator
-    do_register_validator(&mut deps, validator.clone());
-
-    let bob = String::from("bob");
-    let bond_msg = ExecuteMsg::BondForStAtom {};
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
-
-    // set bob's balance to 10 in token contract
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &bond_amount)]),
-        (&String::from("token"), &[]),
-    ]);
-
-    let delegate = &res.messages[0];
-    match delegate.msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
-            assert_eq!(amount, coin(bond_amount.u128(), "uatom"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
-
-    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uatom");
-
-    let res = execute_unbond_statom(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(0u64),
-        },
-    )]);
-
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
+    // the timelock itself is enabled immediately
+    let enable_timelock = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: Some(100),
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    let info = mock_info(&owner, &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), enable_timelock).unwrap();
 
-    // set the block time 30 seconds from now.
-    env.block.time = env.block.time.plus_seconds(31);
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
+    let update_params = UpdateParams {
+        epoch_period: Some(20),
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    let res = execute(
         deps.as_mut(),
-        env.clone(),
+        mock_env(),
         info.clone(),
-        wdraw_unbonded_msg.clone(),
+        update_params.clone(),
+    )
+    .unwrap();
+    let execute_after = mock_env().block.time.seconds() + 100;
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "enqueue_change"),
+            attr("id", "0"),
+            attr("execute_after", execute_after.to_string()),
+        ]
     );
-    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 30);
+
+    let pending: PendingChangesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uatom assets are available yet")
+        pending.changes,
+        vec![PendingChange {
+            id: 0,
+            msg: PendingChangeMsg::UpdateParams {
+                epoch_period: Some(20),
+                unbonding_period: None,
+                liquidity_buffer_target: None,
+                instant_unbond_fee: None,
+                max_insurance_cover: None,
+                min_bond_amount: None,
+                max_exchange_rate_increase: None,
+                timelock_period: None,
+                max_guardian_pause_duration: None,
+                guardian_pause_cooldown: None,
+            },
+            execute_after,
+        }]
     );
 
-    // trigger undelegation message
-    let res =
-        execute_unbond_statom(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(9000u64))])]);
-
-    // this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::from(0u64));
-
-    env.block.time = env.block.time.plus_seconds(91);
+    // the change can't be executed during the timelock period
+    let anyone = mock_info("anyone", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        anyone.clone(),
+        ExecuteMsg::ExecutePendingChange { id: 0 },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(format!(
+            "pending change 0 can't be executed before {}",
+            execute_after
+        ))
+    );
 
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(900u64),
-        },
-    )]);
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        anyone.clone(),
+        ExecuteMsg::ExecutePendingChange { id: 0 },
+    )
+    .unwrap();
 
-    // first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-    };
-    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    // the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::from(1000u64));
-    assert_eq!(res.requests[0].0, 1);
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
 
-    // check with query
-    // this query does not reflect the actual withdrawable
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
+    // a config update is vetoed by a guardian
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: None,
+        validators_registry_contract: Some(String::from("new_registry")),
+        statom_token_contract: None,
+        claims_nft_contract: None,
     };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::from(1000u64));
+    execute(deps.as_mut(), mock_env(), info.clone(), update_config).unwrap();
 
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        anyone.clone(),
+        ExecuteMsg::VetoPendingChange { id: 1 },
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
 
-    assert_eq!(success_res.messages.len(), 1);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AddGuardians {
+            addresses: vec![guardian.clone()],
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&guardian, &[]),
+        ExecuteMsg::VetoPendingChange { id: 1 },
+    )
+    .unwrap();
 
-    let sent_message = &success_res.messages[0];
-    match sent_message.msg.clone() {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, bob);
-            assert_eq!(amount[0].amount, Uint128::from(899u64))
-        }
+    let res = execute(
+        deps.as_mut(),
+        env,
+        anyone,
+        ExecuteMsg::ExecutePendingChange { id: 1 },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("pending change 1 not found")
+    );
 
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config.validators_registry_contract,
+        Some(String::from("validators_registry"))
+    );
 
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+    let pending: PendingChangesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(pending.changes.is_empty());
 }
 
-/// Covers withdraw_unbonded/inactivity in the system while there are slashing events.
+/// Covers if the unbond request is recorded for the recipient instead of the sender.
 #[test]
-pub fn proper_withdraw_unbonded_respect_inactivity_slashing_statom() {
+pub fn proper_unbond_statom_to_recipient() {
     let mut deps = dependencies(&[]);
-
     let validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
-    let bond_amount = Uint128::from(10000u64);
-    let unbond_amount = Uint128::from(500u64);
-
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
@@ -1784,606 +2059,2861 @@ pub fn proper_withdraw_unbonded_respect_inactivity_slashing_statom() {
         reward_contract,
         statom_token_contract.clone(),
     );
-
-    // register_validator
     do_register_validator(&mut deps, validator.clone());
 
-    let bob = String::from("bob");
-    let bond_msg = ExecuteMsg::BondForStAtom {};
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
+    let router = String::from("router");
+    let alice = String::from("alice");
+    do_bond_statom(&mut deps, router.clone(), Uint128::from(10u64));
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&router, &Uint128::from(10u128))])]);
+    set_delegation(&mut deps.querier, validator, 10, "uatom");
 
-    // set bob's balance to 10 in token contract
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &bond_amount)]),
-        (&String::from("token"), &[]),
-    ]);
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: router.clone(),
+        amount: Uint128::from(4u64),
+        msg: to_binary(&Unbond {
+            recipient: Some(alice.clone()),
+            mint_claim: false,
+        })
+        .unwrap(),
+    });
+    let token_info = mock_info(&statom_token_contract, &[]);
+    let res = execute(deps.as_mut(), mock_env(), token_info, receive).unwrap();
+    assert_eq!(1, res.messages.len());
+    assert_eq!(res.attributes[1], attr("from", router.clone()));
+    assert_eq!(res.attributes[2], attr("recipient", alice.clone()));
 
-    let delegate = &res.messages[0];
-    match delegate.msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
-            assert_eq!(amount, coin(bond_amount.u128(), "uatom"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
+    let wait_list = read_unbond_wait_list(&deps.storage, 1, alice.clone()).unwrap();
+    assert_eq!(Uint128::from(4u64), wait_list.statom_amount);
+    assert!(read_unbond_wait_list(&deps.storage, 1, router.clone()).is_err());
 
-    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uatom");
+    let query_unbond: UnbondRequestsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), UnbondRequests { address: alice }).unwrap())
+            .unwrap();
+    assert_eq!(query_unbond.requests, vec![(1u64, Uint128::from(4u64))]);
+}
 
-    let res = execute_unbond_statom(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
+/// Covers minting an unbond request as a cw721 claim and paying
+/// the released claim out to its current owner.
+#[test]
+pub fn proper_unbond_statom_claim_nft() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
 
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
-        (&String::from("token"), &[]),
-    ]);
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+    let claims_contract = String::from("claims_nft");
 
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(0u64),
-        },
-    )]);
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
 
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-    // set the block time 30 seconds from now.
+    let bob = String::from("bob");
+    let alice = String::from("alice");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(100u128))])]);
+    set_delegation(&mut deps.querier, validator.clone(), 100, "uatom");
 
-    let current_batch = CurrentBatch {};
-    let query_batch: CurrentBatchResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
-    assert_eq!(query_batch.id, 1);
-    assert_eq!(query_batch.requested_statom, unbond_amount);
+    let token_info = mock_info(&statom_token_contract, &[]);
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob.clone(),
+        amount: Uint128::from(10u64),
+        msg: to_binary(&Unbond {
+            recipient: None,
+            mint_claim: true,
+        })
+        .unwrap(),
+    });
 
-    env.block.time = env.block.time.plus_seconds(1000);
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
+    // the claims contract must be registered first
+    let res = execute(
         deps.as_mut(),
         mock_env(),
-        info.clone(),
-        wdraw_unbonded_msg.clone(),
+        token_info.clone(),
+        receive.clone(),
     );
-    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
     assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uatom assets are available yet")
+        res.unwrap_err(),
+        StdError::generic_err("the claims nft contract must have been registered")
     );
 
-    // trigger undelegation message
-    let res =
-        execute_unbond_statom(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(9000u64))])]);
-
-    let current_batch = CurrentBatch {};
-    let query_batch: CurrentBatchResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
-    assert_eq!(query_batch.id, 2);
-    assert_eq!(query_batch.requested_statom, Uint128::zero());
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: None,
+        validators_registry_contract: None,
+        statom_token_contract: None,
+        claims_nft_contract: Some(claims_contract.clone()),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_config,
+    )
+    .unwrap();
 
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute(deps.as_mut(), env.clone(), token_info, receive).unwrap();
+    assert_eq!(3, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: DEFAULT_VALIDATOR.to_string(),
+            amount: coin(10, "uatom"),
+        })
+    );
+    let claim = UnbondClaim {
+        batch_id: 1,
+        statom_amount: Uint128::from(10u64),
     };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
-    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "1");
-    assert!(
-        !res.history[0].released,
-        "res.history[0].released is not true"
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: claims_contract.clone(),
+            msg: to_binary(&ClaimsNftExecuteMsg::Mint(ClaimMintMsg {
+                token_id: "1".to_string(),
+                owner: bob.clone(),
+                token_uri: None,
+                extension: claim,
+            }))
+            .unwrap(),
+            funds: vec![],
+        })
     );
-    assert_eq!(res.history[0].batch_id, 1);
 
-    // this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::zero());
+    // the request is not in bob's wait list
+    assert!(read_unbond_wait_list(&deps.storage, 1, bob.clone()).is_err());
 
-    env.block.time = env.block.time.plus_seconds(1091);
+    let query_claim = QueryMsg::UnbondClaim {
+        token_id: "1".to_string(),
+    };
+    let claim: UnbondClaimResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_claim.clone()).unwrap()).unwrap();
+    assert_eq!(claim.batch_id, 1);
+    assert_eq!(claim.statom_amount, Uint128::from(10u64));
+    assert!(!claim.released);
+
+    // bob sold the claim to alice, who sends it to the hub
+    let withdraw = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: alice.clone(),
+        token_id: "1".to_string(),
+        msg: to_binary(&Cw721HookMsg::WithdrawUnbonded { recipient: None }).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&alice, &[]),
+        withdraw.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
 
-    // fabricate balance of the hub contract
     deps.querier.with_native_balances(&[(
         String::from(MOCK_CONTRACT_ADDR),
         Coin {
             denom: "uatom".to_string(),
-            amount: Uint128::from(900u64),
+            amount: Uint128::from(10u64),
         },
     )]);
-    // first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-    };
-    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    // the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::from(1000u64));
-    assert_eq!(res.requests[0].0, 1);
-
+    let claims_info = mock_info(&claims_contract, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        claims_info.clone(),
+        withdraw.clone(),
+    );
+    let not_released = StdError::generic_err(
+        "Unbond claim 1 is not released yet, release its batch with ProcessWithdrawRate",
+    );
+    assert_eq!(res.unwrap_err(), not_released);
+
+    // the claim does not release its batch once the unbonding period has passed
+    env.block.time = env.block.time.plus_seconds(3);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        claims_info.clone(),
+        withdraw.clone(),
+    );
+    assert_eq!(res.unwrap_err(), not_released);
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&alice, &[]),
+        ExecuteMsg::ProcessWithdrawRate { limit: Some(1) },
+    )
+    .unwrap();
+    let res = execute(deps.as_mut(), env, claims_info, withdraw).unwrap();
+    assert_eq!(2, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: claims_contract,
+            msg: to_binary(&ClaimsNftExecuteMsg::Burn {
+                token_id: "1".to_string()
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: alice,
+            amount: coins(10, "uatom"),
+        })
+    );
+
+    // the claim can not be withdrawn twice
+    assert!(query(deps.as_ref(), mock_env(), query_claim).is_err());
+}
+
+/// Covers filling the liquidity buffer on bond and paying out
+/// instant unbonds from it, including the fee and an insufficient buffer.
+#[test]
+pub fn proper_instant_unbond_statom() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    // the fee can not be greater than 1
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: Some(Uint128::from(100u128)),
+        instant_unbond_fee: Some(Decimal::percent(101)),
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    let owner_info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), owner_info.clone(), update_params);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("instant_unbond_fee must not be greater than 1")
+    );
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: Some(Uint128::from(100u128)),
+        instant_unbond_fee: Some(Decimal::percent(1)),
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    execute(deps.as_mut(), mock_env(), owner_info, update_params).unwrap();
+
+    // the first 100 coins are kept in the buffer, the rest is delegated
+    let bob = String::from("bob");
+    let info = mock_info(&bob, &[coin(1000, "uatom")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    match res.messages[0].msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
+            assert_eq!(amount, coin(900, "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", res.messages[0]),
+    }
+
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.total_bond_statom_amount, Uint128::from(900u128));
+    assert_eq!(query_state.liquidity_buffer, Uint128::from(100u128));
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(100u128));
+    assert_eq!(query_state.statom_exchange_rate, Decimal::one());
+
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(1000u128))])]);
+    set_delegation(&mut deps.querier, validator, 900, "uatom");
+
+    // instant unbond is only accepted from the token contract
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob.clone(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&InstantUnbond {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&bob, &[]),
+        receive.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let token_info = mock_info(&statom_token_contract, &[]);
+    let res = execute(deps.as_mut(), mock_env(), token_info.clone(), receive).unwrap();
+    assert_eq!(2, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: statom_token_contract.clone(),
+            msg: to_binary(&Burn {
+                amount: Uint128::from(100u128)
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: bob.clone(),
+            amount: coins(99, "uatom"),
+        })
+    );
+    assert_eq!(res.attributes[3].value, "1");
+    assert_eq!(res.attributes[4].value, "99");
+
+    // the fee stays in the buffer and raises the exchange rate
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.liquidity_buffer, Uint128::from(1u128));
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(1u128));
+
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(900u128))])]);
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), State {}).unwrap()).unwrap();
+    assert_eq!(
+        query_state.statom_exchange_rate,
+        Decimal::from_ratio(901u128, 900u128)
+    );
+
+    // the buffer is drained now
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob,
+        amount: Uint128::from(10u128),
+        msg: to_binary(&InstantUnbond {}).unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), token_info, receive);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(
+            "Not enough uatom in the liquidity buffer: 10 requested, 1 available"
+        )
+    );
+}
+
+#[test]
+pub fn proper_undelegate() {
+    let mut deps = dependencies(&[]);
+
+    let addr1 = String::from("addr1000");
+    let addr2 = String::from("addr2000");
+    let addr3 = String::from("addr3000");
+
+    // create 3 validators
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2);
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3);
+
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    do_register_validator(&mut deps, validator.clone());
+    do_register_validator(&mut deps, validator2.clone());
+    do_register_validator(&mut deps, validator3.clone());
+
+    // bond to a validator
+    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(10u64));
+    do_bond_statom(&mut deps, addr2.clone(), Uint128::from(150u64));
+    do_bond_statom(&mut deps, addr3.clone(), Uint128::from(200u64));
+
+    // give validators different delegation amount
+    let delegations: [FullDelegation; 3] = [
+        (sample_delegation(validator.address.clone(), coin(10, "uatom"))),
+        (sample_delegation(validator2.address.clone(), coin(150, "uatom"))),
+        (sample_delegation(validator3.address.clone(), coin(200, "uatom"))),
+    ];
+
+    let validators: [Validator; 3] = [(validator), (validator2.clone()), (validator3.clone())];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[
+            (&addr3, &Uint128::from(200u64)),
+            (&addr2, &Uint128::from(150u64)),
+            (&addr1, &Uint128::from(10u64)),
+        ],
+    )]);
+
+    // send the first burn
+    let token_info = mock_info(&statom_token_contract, &[]);
+    let mut env = mock_env();
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2.clone(),
+        env.clone(),
+        token_info.clone(),
+        Uint128::from(50u64),
+    );
+    assert_eq!(res.messages.len(), 1);
+
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[
+            (&addr3, &Uint128::from(200u64)),
+            (&addr2, &Uint128::from(100u64)),
+            (&addr1, &Uint128::from(10u64)),
+        ],
+    )]);
+
+    env.block.time = env.block.time.plus_seconds(40);
+
+    // send the second burn
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2.clone(),
+        env,
+        token_info,
+        Uint128::from(100u64),
+    );
+
+    assert_eq!(res.messages.len(), 3);
+
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[
+            (&addr3, &Uint128::from(200u64)),
+            (&addr2, &Uint128::from(0u64)),
+            (&addr1, &Uint128::from(10u64)),
+        ],
+    )]);
+
+    // check if the undelegate message is send two more than one validator.
+    match &res.messages[0].msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: val,
+            amount,
+        }) => {
+            assert_eq!(val, &validator3.address);
+            assert_eq!(amount.amount, Uint128::from(130u64));
+        }
+        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
+    }
+    match &res.messages[1].msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: val,
+            amount,
+        }) => {
+            assert_eq!(val, &validator2.address);
+            assert_eq!(amount.amount, Uint128::from(20u64));
+        }
+        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
+    }
+}
+
+/// Covers if the undelegate function sends different Undelegate messages
+/// if the delegations of the user are distributed to several validators
+/// and if the user wants to unbond amount that none of validators has.
+#[test]
+pub fn proper_undelegate_respect_distributed_delegation() {
+    let mut deps = dependencies(&[]);
+
+    let addr1 = String::from("addr1000");
+    let addr2 = String::from("addr2000");
+
+    // create 3 validators
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2);
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3);
+
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    do_register_validator(&mut deps, validator.clone());
+    do_register_validator(&mut deps, validator2.clone());
+    do_register_validator(&mut deps, validator3);
+
+    // bond to a validator
+    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1000u64));
+    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1500u64));
+
+    // give validators different delegation amount
+    let delegations: [FullDelegation; 2] = [
+        (sample_delegation(validator.address.clone(), coin(1000, "uatom"))),
+        (sample_delegation(validator2.address.clone(), coin(1500, "uatom"))),
+    ];
+
+    let validators: [Validator; 2] = [(validator), (validator2)];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    deps.querier.with_token_balances(&[
+        (&String::from("token"), &[(&addr1, &Uint128::from(2500u64))]),
+        (&statom_token_contract, &[]),
+    ]);
+
+    // send the first burn
+    let token_info = mock_info(&statom_token_contract, &[]);
+    let mut env = mock_env();
+
+    env.block.time = env.block.time.plus_seconds(40);
+
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2,
+        env,
+        token_info,
+        Uint128::from(2000u64),
+    );
+    assert_eq!(res.messages.len(), 3);
+
+    match &res.messages[0].msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: _,
+            amount,
+        }) => assert_eq!(amount.amount, Uint128::from(1250u64)),
+        _ => panic!("Unexpected message: {:?}", &res.messages[0]),
+    }
+}
+
+/// Covers the effect of slashing of bond, unbond, and withdraw_unbonded
+/// update the exchange rate after and before slashing.
+#[test]
+pub fn proper_slashing_statom() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let addr1 = String::from("addr1000");
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    // register_validator
+    do_register_validator(&mut deps, validator.clone());
+
+    // bond
+    do_bond_statom(&mut deps, addr1.clone(), Uint128::from(1000u64));
+
+    // this will set the balance of the user in token contract
+    deps.querier.with_token_balances(&[
+        (
+            &statom_token_contract,
+            &[(&addr1, &Uint128::from(1000u128))],
+        ),
+        (&String::from("token"), &[]),
+    ]);
+
+    // slashing
+    set_delegation(&mut deps.querier, validator.clone(), 900, "uatom");
+
+    let info = mock_info(&addr1, &[]);
+    let report_slashing = CheckSlashing {};
+    let res = execute(deps.as_mut(), mock_env(), info, report_slashing).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let ex_rate = State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(query_exchange_rate.statom_exchange_rate.to_string(), "0.9");
+
+    // bond again to see the update exchange rate
+    let second_bond = ExecuteMsg::BondForStAtom {};
+
+    let info = mock_info(&addr1, &[coin(900, "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), second_bond).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    let expected_er = "0.9";
+    let ex_rate = State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(
+        query_exchange_rate.statom_exchange_rate.to_string(),
+        expected_er
+    );
+
+    let delegate = &res.messages[0];
+    match delegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
+            assert_eq!(amount, coin(900, "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    let message = &res.messages[1];
+    match message.msg.clone() {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds: _,
+        }) => {
+            assert_eq!(contract_addr, statom_token_contract);
+            assert_eq!(
+                msg,
+                to_binary(&Mint {
+                    recipient: info.sender.to_string(),
+                    amount: Uint128::from(1000u64)
+                })
+                .unwrap()
+            );
+        }
+        _ => panic!("Unexpected message: {:?}", message),
+    }
+
+    set_delegation(&mut deps.querier, validator.clone(), 1800, "uatom");
+
+    // update user balance
+    deps.querier.with_token_balances(&[
+        (
+            &statom_token_contract,
+            &[(&addr1, &Uint128::from(2000u128))],
+        ),
+        (&String::from("token"), &[]),
+    ]);
+
+    let info = mock_info(&addr1, &[]);
+    let mut env = mock_env();
+    let _res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        Uint128::from(500u64),
+        addr1.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &statom_token_contract,
+            &[(&addr1, &Uint128::from(1500u128))],
+        ),
+        (&String::from("token"), &[]),
+    ]);
+
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        Uint128::from(500u64),
+        addr1.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    let msgs: CosmosMsg = CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: validator.address,
+        amount: coin(900, "uatom"),
+    });
+    assert_eq!(res.messages[0].msg, msgs);
+
+    deps.querier.with_token_balances(&[(
+        &statom_token_contract,
+        &[(&addr1, &Uint128::from(1000u128))],
+    )]);
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(900u64),
+        },
+    )]);
+
+    let ex_rate = State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(
+        query_exchange_rate.statom_exchange_rate.to_string(),
+        expected_er
+    );
+
+    env.block.time = env.block.time.plus_seconds(90);
+    // check withdrawUnbonded message, the coins are sent to the recipient
+    let vault = String::from("vault");
+    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {
+        recipient: Some(vault.clone()),
+        batch_ids: None,
+        limit: None,
+    };
+    let wdraw_unbonded_res = execute(deps.as_mut(), env, info, withdraw_unbond_msg).unwrap();
+    assert_eq!(wdraw_unbonded_res.messages.len(), 1);
+
+    let ex_rate = State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(
+        query_exchange_rate.statom_exchange_rate.to_string(),
+        expected_er
+    );
+
+    let sent_message = &wdraw_unbonded_res.messages[0];
+    match sent_message.msg.clone() {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, vault);
+            assert_eq!(amount[0].amount, Uint128::from(900u64))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+}
+
+/// Covers if the current batch is submitted without a new unbond request
+/// once the epoch period has passed.
+#[test]
+pub fn proper_submit_batch() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let keeper = String::from("keeper");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    set_delegation(&mut deps.querier, validator.clone(), 100, "uatom");
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(100u128))])]);
+
+    // there is nothing to submit yet
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&keeper, &[]),
+        ExecuteMsg::SubmitBatch {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("The current batch has no unbond requests")
+    );
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        Uint128::from(10u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(90u128))])]);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&keeper, &[]),
+        ExecuteMsg::SubmitBatch {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("The epoch period has not passed yet, 21 seconds left")
+    );
+
+    env.block.time = env.block.time.plus_seconds(21);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&keeper, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator.address,
+            amount: coin(10, "uatom"),
+        })
+    );
+    assert_eq!(res.attributes[1], attr("batch_id", "1"));
+
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), CurrentBatch {}).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 2);
+    assert_eq!(query_batch.requested_statom, Uint128::zero());
+
+    let res: AllHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            AllHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.history.len(), 1);
+    assert_eq!(res.history[0].statom_amount, Uint128::from(10u64));
+    assert_eq!(res.history[0].time, env.block.time.seconds());
+
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.last_unbonded_time, env.block.time.seconds());
+    assert_eq!(query_state.total_bond_statom_amount, Uint128::from(90u64));
+}
+
+/// Covers paying the share of the liquidity buffer in a batch from the buffer,
+/// so only the delegated share is undelegated, and withdrawing the whole batch.
+#[test]
+pub fn proper_submit_batch_with_liquidity_buffer() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: Some(Uint128::from(600u128)),
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    // 600 coins are kept in the buffer, only 400 are delegated
+    let bob = String::from("bob");
+    let info = mock_info(&bob, &[coin(1000, "uatom")]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::BondForStAtom {},
+    )
+    .unwrap();
+    set_delegation(&mut deps.querier, validator.clone(), 400, "uatom");
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(1000u128))])]);
+
+    // 800 coins are unbonded, more than delegated: 480 of them are paid from the buffer
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        Uint128::from(800u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator.address,
+            amount: coin(320, "uatom"),
+        })
+    );
+
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(200u128))])]);
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.total_bond_statom_amount, Uint128::from(80u64));
+    assert_eq!(query_state.liquidity_buffer, Uint128::from(120u64));
+    assert_eq!(query_state.statom_exchange_rate, Decimal::one());
+
+    // the undelegated coins are back next to the buffer
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(920u64),
+        },
+    )]);
+
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), withdraw).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: bob,
+            amount: coins(800, "uatom"),
+        })
+    );
+
+    // the rest of the buffer stays out of the next releases
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env, State {}).unwrap()).unwrap();
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(120u64));
+    assert_eq!(query_state.liquidity_buffer, Uint128::from(120u64));
+}
+
+/// Covers releasing the batches and withdrawing them in chunks.
+#[test]
+pub fn proper_withdraw_unbonded_in_chunks() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    set_delegation(&mut deps.querier, validator, 100, "uatom");
+
+    // unbond in three batches
+    let mut env = mock_env();
+    for i in 1..=3u128 {
+        deps.querier.with_token_balances(&[(
+            &statom_token_contract,
+            &[(&bob, &Uint128::from(110 - 10 * i))],
+        )]);
+        env.block.time = env.block.time.plus_seconds(31);
+        let res = execute_unbond_statom(
+            deps.as_mut(),
+            env.clone(),
+            Uint128::from(10u64),
+            bob.clone(),
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+    }
+
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(30u64),
+        },
+    )]);
+
+    // release the first batch only
+    let process = ExecuteMsg::ProcessWithdrawRate { limit: Some(1) };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), process).unwrap();
+    assert_eq!(res.attributes[1], attr("last_processed_batch", "1"));
+    assert_eq!(res.attributes[2], attr("completed", "false"));
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(10u64));
+
+    // releases the second batch and withdraws the first one
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), withdraw).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: bob.clone(),
+            amount: coins(10, "uatom"),
+        })
+    );
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.last_processed_batch, 2);
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(10u64));
+    assert!(read_unbond_wait_list(&deps.storage, 1, bob.clone()).is_err());
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(20u64),
+        },
+    )]);
+
+    // the unknown batch is skipped, the rest is released and withdrawn
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: Some(vec![3, 2, 7]),
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), withdraw).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: bob.clone(),
+            amount: coins(20, "uatom"),
+        })
+    );
+
+    let all_history = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), env, all_history).unwrap()).unwrap();
+    assert_eq!(res.history.len(), 3);
+    for history in res.history {
+        assert!(history.released);
+        assert_eq!(history.statom_withdraw_rate, Decimal::one());
+    }
+    let query_unbond: UnbondRequestsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), UnbondRequests { address: bob }).unwrap())
+            .unwrap();
+    assert!(query_unbond.requests.is_empty());
+}
+
+/// Covers a slashing during unbonding shared by the batches released in separate chunks.
+#[test]
+pub fn proper_withdraw_unbonded_in_chunks_respect_slashing() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(200u64));
+    set_delegation(&mut deps.querier, validator, 200, "uatom");
+
+    // unbond in two batches of 100
+    let mut env = mock_env();
+    for i in 1..=2u128 {
+        deps.querier.with_token_balances(&[(
+            &statom_token_contract,
+            &[(&bob, &Uint128::from(300 - 100 * i))],
+        )]);
+        env.block.time = env.block.time.plus_seconds(31);
+        execute_unbond_statom(
+            deps.as_mut(),
+            env.clone(),
+            Uint128::from(100u64),
+            bob.clone(),
+            None,
+            false,
+        )
+        .unwrap();
+    }
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.unbonding_amount, Uint128::from(200u64));
+
+    // each batch gets back 90 instead of 100
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(180u64),
+        },
+    )]);
+
+    let process = ExecuteMsg::ProcessWithdrawRate { limit: Some(1) };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), process).unwrap();
+    assert_eq!(res.attributes[2], attr("completed", "false"));
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(90u64));
+    assert_eq!(query_state.unbonding_amount, Uint128::from(100u64));
+
+    let process = ExecuteMsg::ProcessWithdrawRate { limit: None };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), process).unwrap();
+    assert_eq!(res.attributes[2], attr("completed", "true"));
+
+    let all_history = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), env, all_history).unwrap()).unwrap();
+    // both batches bear the same share of the slashing, less the unit kept for rounding
+    assert_eq!(res.history.len(), 2);
+    for history in res.history {
+        assert!(history.released);
+        assert_eq!(history.statom_withdraw_rate, Decimal::percent(89));
+    }
+}
+
+/// Covers withdrawing on behalf of the users and skipping the ones which opted out.
+#[test]
+pub fn proper_withdraw_unbonded_for() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let alice = String::from("alice");
+    let carol = String::from("carol");
+    let keeper = String::from("keeper");
+    do_bond_statom(&mut deps, bob.clone(), Uint128::from(100u64));
+    set_delegation(&mut deps.querier, validator, 100, "uatom");
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&carol, &[]),
+        ExecuteMsg::SetWithdrawOptOut { opt_out: true },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("opt_out", "true"));
+
+    // all of them unbond in the first batch
+    let mut env = mock_env();
+    for (i, user) in [&bob, &alice, &carol].iter().enumerate() {
+        deps.querier.with_token_balances(&[(
+            &statom_token_contract,
+            &[(&bob, &Uint128::from(100 - 10 * i as u128))],
+        )]);
+        if i == 2 {
+            env.block.time = env.block.time.plus_seconds(31);
+        }
+        execute_unbond_statom(
+            deps.as_mut(),
+            env.clone(),
+            Uint128::from(10u64),
+            user.to_string(),
+            None,
+            false,
+        )
+        .unwrap();
+    }
+
+    env.block.time = env.block.time.plus_seconds(3);
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(30u64),
+        },
+    )]);
+
+    let withdraw_for = ExecuteMsg::WithdrawUnbondedFor {
+        addresses: vec![bob.clone(), alice.clone(), carol.clone()],
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&keeper, &[]),
+        withdraw_for.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .iter()
+            .map(|m| m.msg.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: bob.clone(),
+                amount: coins(10, "uatom"),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: alice,
+                amount: coins(10, "uatom"),
+            }),
+        ]
+    );
+    assert_eq!(res.attributes[3], attr("opted_out", carol.clone()));
+    assert_eq!(res.attributes[4], attr("amount", "20"));
+
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), State {}).unwrap()).unwrap();
+    assert_eq!(query_state.prev_hub_balance, Uint128::from(10u64));
+
+    // nothing is left to withdraw on behalf of the users
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(10u64),
+        },
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&keeper, &[]),
+        withdraw_for,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No withdrawable uatom assets are available yet")
+    );
+
+    // carol still withdraws directly
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env, mock_info(&carol, &[]), withdraw).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: carol,
+            amount: coins(10, "uatom"),
+        })
+    );
+}
+
+/// Covers if the withdraw_rate function is updated before and after withdraw_unbonded,
+/// the finished amount is accurate, user requests are removed from the waitlist, and
+/// the BankMsg::Send is sent.
+#[test]
+pub fn proper_withdraw_unbonded_statom() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    // register_validator
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let bond_msg = ExecuteMsg::BondForStAtom {};
+
+    let info = mock_info(&bob, &[coin(100, "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    // set bob's balance to 10 in token contract
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(100u128))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    let delegate = &res.messages[0];
+    match delegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
+            assert_eq!(amount, coin(100, "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    let bond_msg = ExecuteMsg::BondRewards {};
+
+    let info = mock_info(&String::from("reward"), &[coin(100, "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(1, res.messages.len());
+
+    set_delegation(&mut deps.querier, validator, 200, "uatom");
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        Uint128::from(10u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(90u128))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(0u64),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+    // set the block time 30 seconds from now.
+    env.block.time = env.block.time.plus_seconds(31);
+
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
+
+    // trigger undelegation message
+    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uatom assets are available yet")
+    );
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        Uint128::from(10u64),
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(80u128))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    let state = State {};
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
+    assert_eq!(query_state.total_bond_statom_amount, Uint128::from(160u64));
+    assert_eq!(
+        query_state.statom_exchange_rate,
+        Decimal::from_ratio(2u128, 1u128)
+    );
+
+    // this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::from(0u64));
+
+    env.block.time = env.block.time.plus_seconds(91);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(40u64),
+        },
+    )]);
+    // first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+    };
+    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    // the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::from(20u64));
+    assert_eq!(res.requests[0].0, 1);
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].statom_amount, Uint128::from(20u64));
+    assert_eq!(res.history[0].batch_id, 1);
+
+    // check with query
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::from(40u64));
+
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0];
+    match sent_message.msg.clone() {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, bob);
+            assert_eq!(amount[0].amount, Uint128::from(40u64))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // it should be removed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+
+    let waitlist = UnbondRequests {
+        address: bob.clone(),
+    };
+    let query_unbond: UnbondRequestsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), waitlist).unwrap()).unwrap();
+    assert_eq!(
+        query_unbond,
+        UnbondRequestsResponse {
+            address: bob,
+            requests: vec![]
+        }
+    );
+
+    // because of one that we add for each batch
+    let state = State {};
+    let state_query: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
+    assert_eq!(state_query.prev_hub_balance, Uint128::from(0u64));
+}
+
+/// Covers slashing during the unbonded period and its effect on the finished amount.
+#[test]
+pub fn proper_withdraw_unbonded_respect_slashing_statom() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::from(10000u64);
+    let unbond_amount = Uint128::from(500u64);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    // register_validator
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let bond_msg = ExecuteMsg::BondForStAtom {};
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    // set bob's balance to 10 in token contract
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &bond_amount)]),
+        (&String::from("token"), &[]),
+    ]);
+
+    let delegate = &res.messages[0];
+    match delegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
+            assert_eq!(amount, coin(bond_amount.u128(), "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uatom");
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(0u64),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+
+    // set the block time 30 seconds from now.
+    env.block.time = env.block.time.plus_seconds(31);
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
+    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uatom assets are available yet")
+    );
+
+    // trigger undelegation message
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(9000u64))])]);
+
+    // this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::from(0u64));
+
+    env.block.time = env.block.time.plus_seconds(91);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(900u64),
+        },
+    )]);
+
+    // first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+    };
+    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    // the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::from(1000u64));
+    assert_eq!(res.requests[0].0, 1);
+
+    // check with query
+    // this query does not reflect the actual withdrawable
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::from(1000u64));
+
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0];
+    match sent_message.msg.clone() {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, bob);
+            assert_eq!(amount[0].amount, Uint128::from(899u64))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+}
+
+/// Covers withdraw_unbonded/inactivity in the system while there are slashing events.
+#[test]
+pub fn proper_withdraw_unbonded_respect_inactivity_slashing_statom() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::from(10000u64);
+    let unbond_amount = Uint128::from(500u64);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    // register_validator
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let bond_msg = ExecuteMsg::BondForStAtom {};
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    // set bob's balance to 10 in token contract
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &bond_amount)]),
+        (&String::from("token"), &[]),
+    ]);
+
+    let delegate = &res.messages[0];
+    match delegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR);
+            assert_eq!(amount, coin(bond_amount.u128(), "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uatom");
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(0u64),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+    // set the block time 30 seconds from now.
+
+    let current_batch = CurrentBatch {};
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 1);
+    assert_eq!(query_batch.requested_statom, unbond_amount);
+
+    env.block.time = env.block.time.plus_seconds(1000);
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
+    assert!(wdraw_unbonded_res.is_err(), "unbonded error");
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uatom assets are available yet")
+    );
+
+    // trigger undelegation message
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&statom_token_contract, &[(&bob, &Uint128::from(9000u64))])]);
+
+    let current_batch = CurrentBatch {};
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 2);
+    assert_eq!(query_batch.requested_statom, Uint128::zero());
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
+    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "1");
+    assert!(
+        !res.history[0].released,
+        "res.history[0].released is not true"
+    );
+    assert_eq!(res.history[0].batch_id, 1);
+
+    // this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::zero());
+
+    env.block.time = env.block.time.plus_seconds(1091);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(900u64),
+        },
+    )]);
+    // first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+    };
+    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    // the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::from(1000u64));
+    assert_eq!(res.requests[0].0, 1);
+
     // check with query
     // this query does not reflect the actual withdrawable
     let withdrawable = WithdrawableUnbonded {
         address: bob.clone(),
     };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::from(1000u64));
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::from(1000u64));
+
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0];
+    match sent_message.msg.clone() {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, bob);
+            assert_eq!(amount[0].amount, Uint128::from(899u64))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
+    assert_eq!(res.history[0].statom_applied_exchange_rate.to_string(), "1");
+    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "0.899");
+    assert!(
+        res.history[0].released,
+        "res.history[0].released is not true"
+    );
+    assert_eq!(res.history[0].batch_id, 1);
+}
+
+/// Covers if the signed integer works properly,
+/// the exception when a user sends rogue coin.
+#[test]
+pub fn proper_withdraw_unbond_with_dummies_statom() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::from(10000u64);
+    let unbond_amount = Uint128::from(500u64);
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract.clone(),
+    );
+
+    // register_validator
+    do_register_validator(&mut deps, validator.clone());
+
+    let bob = String::from("bob");
+    let bond_msg = ExecuteMsg::BondForStAtom {};
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    // set bob's balance to 10 in token contract
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &bond_amount)]),
+        (&String::from("token"), &[]),
+    ]);
+
+    set_delegation(
+        &mut deps.querier,
+        validator.clone(),
+        bond_amount.u128(),
+        "uatom",
+    );
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        mock_env(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(0u64),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+
+    // set the block time 30 seconds from now.
+    env.block.time = env.block.time.plus_seconds(31);
+    // trigger undelegation message
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(9000u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    // slashing
+    set_delegation(
+        &mut deps.querier,
+        validator,
+        bond_amount.u128() - 2000,
+        "uatom",
+    );
+
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(8500u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute_unbond_statom(
+        deps.as_mut(),
+        env.clone(),
+        unbond_amount,
+        bob.clone(),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier.with_token_balances(&[
+        (&statom_token_contract, &[(&bob, &Uint128::from(8000u64))]),
+        (&String::from("token"), &[]),
+    ]);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        String::from(MOCK_CONTRACT_ADDR),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(2200u64),
+        },
+    )]);
+
+    env.block.time = env.block.time.plus_seconds(120);
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        recipient: None,
+        batch_ids: None,
+        limit: None,
+    };
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
+    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "1.164");
+    assert!(
+        res.history[0].released,
+        "res.history[0].released is not true"
+    );
+    assert_eq!(res.history[0].batch_id, 1);
+    assert_eq!(res.history[1].statom_amount, Uint128::from(1000u64));
+    assert_eq!(res.history[1].statom_withdraw_rate.to_string(), "1.033");
+    assert!(
+        res.history[1].released,
+        "res.history[1].released is not true"
+    );
+    assert_eq!(res.history[1].batch_id, 2);
+
+    let expected = (res.history[0].statom_withdraw_rate * res.history[0].statom_amount)
+        + res.history[1].statom_withdraw_rate * res.history[1].statom_amount;
+    let sent_message = &success_res.messages[0];
+    match sent_message.msg.clone() {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, bob);
+            assert_eq!(amount[0].amount, expected)
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+}
+
+/// Covers if the state/parameters storage is updated to the given value,
+/// who sends the message, and if
+/// RewardUpdateDenom message is sent to the reward contract
+#[test]
+pub fn test_update_params() {
+    let mut deps = dependencies(&[]);
+
+    let _validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    // test with no swap denom.
+    let update_prams = UpdateParams {
+        epoch_period: Some(20),
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner,
+        reward_contract,
+        statom_token_contract,
+    );
+
+    let invalid_info = mock_info(String::from("invalid").as_str(), &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        invalid_info,
+        update_prams.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
+    assert_eq!(params.underlying_coin_denom, "uatom");
+    assert_eq!(params.unbonding_period, 2);
+
+    // test with some swap_denom.
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: Some(3),
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
+
+    // the result must be 1
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
+    assert_eq!(params.underlying_coin_denom, "uatom");
+    assert_eq!(params.unbonding_period, 3);
+
+    // the pauses of the guardians always expire
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: Some(0),
+        guardian_pause_cooldown: None,
+    };
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("max_guardian_pause_duration must be greater than 0")
+    );
+}
+
+/// Covers if the storage affected by update_config are updated properly
+#[test]
+pub fn proper_update_config() {
+    let mut deps = dependencies(&[]);
+
+    let _validator = sample_validator(DEFAULT_VALIDATOR);
+    set_validator_mock(&mut deps.querier);
+
+    let owner = String::from("owner1");
+    let new_owner = String::from("new_owner");
+    let invalid_owner = String::from("invalid_owner");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract.clone(),
+        statom_token_contract.clone(),
+    );
+
+    let config = Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+
+    // make sure the other configs are still the same.
+    assert_eq!(
+        &config_query.reward_dispatcher_contract.unwrap(),
+        &reward_contract
+    );
+    assert_eq!(&config_query.owner, &owner);
+
+    // only the owner can propose a new owner
+    let propose_owner = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.clone(),
+        expires_in: 100,
+    };
+    let info = mock_info(&invalid_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, propose_owner.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // an expiry past the end of time is rejected
+    let info = mock_info(&owner, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ProposeNewOwner {
+            owner: new_owner.clone(),
+            expires_in: u64::MAX,
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("expires_in is too large")
+    );
+
+    // a cancelled proposal can't be accepted
+    let info = mock_info(&owner, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        propose_owner.clone(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&new_owner, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("there is no pending ownership proposal")
+    );
+
+    // an expired proposal can't be accepted
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        propose_owner.clone(),
+    )
+    .unwrap();
+    let mut expired = mock_env();
+    expired.block.time = expired.block.time.plus_seconds(101);
+    let res = execute(
+        deps.as_mut(),
+        expired,
+        mock_info(&new_owner, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the ownership proposal has expired")
+    );
+
+    // change the owner
+    let res = execute(deps.as_mut(), mock_env(), info, propose_owner).unwrap();
+    assert_eq!(res.messages.len(), 0);
+    let pending: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap()).unwrap();
+    assert_eq!(
+        pending,
+        PendingOwnerResponse {
+            pending_owner: Some(new_owner.clone()),
+            expires_at: Some(mock_env().block.time.seconds() + 100),
+        }
+    );
+
+    // the owner doesn't change until the proposed owner accepts
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.creator, owner);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&invalid_owner, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&new_owner, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+    let pending: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap()).unwrap();
+    assert_eq!(pending.pending_owner, None);
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    let new_owner_raw = deps.api.addr_validate(&new_owner).unwrap();
+    assert_eq!(new_owner_raw, config.creator);
+
+    // new owner can send the owner related messages
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
 
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
 
-    assert_eq!(success_res.messages.len(), 1);
+    // previous owner cannot send this message
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
+    };
 
-    let sent_message = &success_res.messages[0];
-    match sent_message.msg.clone() {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, bob);
-            assert_eq!(amount[0].amount, Uint128::from(899u64))
+    let new_owner_info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: Some(String::from("new reward")),
+        validators_registry_contract: None,
+        statom_token_contract: None,
+        claims_nft_contract: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let msg: CosmosMsg = CosmosMsg::Distribution(DistributionMsg::SetWithdrawAddress {
+        address: String::from("new reward"),
+    });
+    assert_eq!(msg, res.messages[0].msg.clone());
+
+    let config = Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(
+        config_query.reward_dispatcher_contract.unwrap(),
+        String::from("new reward")
+    );
+
+    let config = Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+
+    // make sure the other configs are still the same.
+    assert_eq!(
+        config_query.reward_dispatcher_contract.unwrap(),
+        String::from("new reward")
+    );
+    assert_eq!(config_query.owner, new_owner);
+
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: None,
+        validators_registry_contract: None,
+        statom_token_contract: None,
+        claims_nft_contract: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: None,
+        validators_registry_contract: Some(String::from("new registry")),
+        statom_token_contract: None,
+        claims_nft_contract: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let config = Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(
+        config_query.validators_registry_contract.unwrap(),
+        String::from("new registry"),
+    );
+
+    let update_config = UpdateConfig {
+        rewards_dispatcher_contract: None,
+        validators_registry_contract: None,
+        statom_token_contract: Some(statom_token_contract.clone()),
+        claims_nft_contract: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("updating stAtom token address is forbidden",)
+    );
+
+    let config = Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(
+        config_query.statom_token_contract.unwrap(),
+        statom_token_contract,
+    );
+}
+
+fn set_delegation(querier: &mut WasmMockQuerier, validator: Validator, amount: u128, denom: &str) {
+    querier.update_staking(
+        "uatom",
+        std::slice::from_ref(&validator),
+        &[sample_delegation(
+            validator.address.clone(),
+            coin(amount, denom),
+        )],
+    );
+}
+
+fn set_delegation_query(
+    querier: &mut WasmMockQuerier,
+    delegate: &[FullDelegation],
+    validators: &[Validator],
+) {
+    querier.update_staking("uatom", validators, delegate);
+}
+
+fn sample_delegation(addr: String, amount: Coin) -> FullDelegation {
+    let can_redelegate = amount.clone();
+    let accumulated_rewards = coins(0, &amount.denom);
+    FullDelegation {
+        validator: addr,
+        delegator: Addr::unchecked(String::from(MOCK_CONTRACT_ADDR)),
+        amount,
+        can_redelegate,
+        accumulated_rewards,
+    }
+}
+
+#[test]
+fn proper_redelegate_proxy() {
+    let mut deps = dependencies(&[]);
+
+    set_validator_mock(&mut deps.querier);
+
+    let addr1 = String::from("addr1000");
+
+    let owner = String::from("owner1");
+    let statom_token_contract = String::from("statom_token");
+    let reward_contract = String::from("reward");
+    let validators_registry = String::from("validators_registry");
+
+    initialize(
+        deps.borrow_mut(),
+        owner.clone(),
+        reward_contract,
+        statom_token_contract,
+    );
+
+    let redelegate_proxy_msg = ExecuteMsg::RedelegateProxy {
+        src_validator: String::from("src_validator"),
+        redelegations: vec![(String::from("dst_validator"), Coin::new(100, "uatom"))],
+    };
+
+    // invalid sender
+    let info = mock_info(&addr1, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        redelegate_proxy_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // check that validators_registry can send such messages
+    let info = mock_info(&validators_registry, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        redelegate_proxy_msg.clone(),
+    )
+    .unwrap();
+
+    let redelegate = &res.messages[0];
+    match redelegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator,
+            dst_validator,
+            amount,
+        }) => {
+            assert_eq!(src_validator, String::from("src_validator"));
+            assert_eq!(dst_validator, String::from("dst_validator"));
+            assert_eq!(amount, Coin::new(100, "uatom"));
+        }
+        _ => panic!("Unexpected message: {:?}", redelegate),
+    }
+
+    // check that creator can send such messages
+    let info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, redelegate_proxy_msg).unwrap();
+
+    let redelegate = &res.messages[0];
+    match redelegate.msg.clone() {
+        CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator,
+            dst_validator,
+            amount,
+        }) => {
+            assert_eq!(src_validator, String::from("src_validator"));
+            assert_eq!(dst_validator, String::from("dst_validator"));
+            assert_eq!(amount, Coin::new(100, "uatom"));
         }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
+        _ => panic!("Unexpected message: {:?}", redelegate),
     }
-
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
-
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
-    assert_eq!(res.history[0].statom_applied_exchange_rate.to_string(), "1");
-    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "0.899");
-    assert!(
-        res.history[0].released,
-        "res.history[0].released is not true"
-    );
-    assert_eq!(res.history[0].batch_id, 1);
 }
 
-/// Covers if the signed integer works properly,
-/// the exception when a user sends rogue coin.
 #[test]
-pub fn proper_withdraw_unbond_with_dummies_statom() {
+pub fn test_pause() {
     let mut deps = dependencies(&[]);
 
-    let validator = sample_validator(DEFAULT_VALIDATOR);
+    let _validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
-    let bond_amount = Uint128::from(10000u64);
-    let unbond_amount = Uint128::from(500u64);
-
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
 
     initialize(
         deps.borrow_mut(),
-        owner,
+        owner.clone(),
         reward_contract,
-        statom_token_contract.clone(),
-    );
-
-    // register_validator
-    do_register_validator(&mut deps, validator.clone());
-
-    let bob = String::from("bob");
-    let bond_msg = ExecuteMsg::BondForStAtom {};
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uatom")]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
-
-    // set bob's balance to 10 in token contract
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &bond_amount)]),
-        (&String::from("token"), &[]),
-    ]);
-
-    set_delegation(
-        &mut deps.querier,
-        validator.clone(),
-        bond_amount.u128(),
-        "uatom",
-    );
-
-    let res = execute_unbond_statom(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(9500u64))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(0u64),
-        },
-    )]);
-
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-
-    // set the block time 30 seconds from now.
-    env.block.time = env.block.time.plus_seconds(31);
-    // trigger undelegation message
-    let res =
-        execute_unbond_statom(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(9000u64))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    // slashing
-    set_delegation(
-        &mut deps.querier,
-        validator,
-        bond_amount.u128() - 2000,
-        "uatom",
+        statom_token_contract,
     );
 
-    let res =
-        execute_unbond_statom(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(8500u64))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    env.block.time = env.block.time.plus_seconds(31);
-    let res =
-        execute_unbond_statom(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier.with_token_balances(&[
-        (&statom_token_contract, &[(&bob, &Uint128::from(8000u64))]),
-        (&String::from("token"), &[]),
-    ]);
-
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        String::from(MOCK_CONTRACT_ADDR),
-        Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(2200u64),
-        },
-    )]);
-
-    env.block.time = env.block.time.plus_seconds(120);
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
-
-    assert_eq!(success_res.messages.len(), 1);
+    // set paused = true
+    let pause_contracts = ExecuteMsg::PauseContracts { reason: None };
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), creator_info, pause_contracts).unwrap();
 
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].statom_amount, Uint128::from(1000u64));
-    assert_eq!(res.history[0].statom_withdraw_rate.to_string(), "1.164");
-    assert!(
-        res.history[0].released,
-        "res.history[0].released is not true"
-    );
-    assert_eq!(res.history[0].batch_id, 1);
-    assert_eq!(res.history[1].statom_amount, Uint128::from(1000u64));
-    assert_eq!(res.history[1].statom_withdraw_rate.to_string(), "1.033");
-    assert!(
-        res.history[1].released,
-        "res.history[1].released is not true"
+    // try to run a not allowed action, should return an error
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    let info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the contract is temporarily paused")
     );
-    assert_eq!(res.history[1].batch_id, 2);
-
-    let expected = (res.history[0].statom_withdraw_rate * res.history[0].statom_amount)
-        + res.history[1].statom_withdraw_rate * res.history[1].statom_amount;
-    let sent_message = &success_res.messages[0];
-    match sent_message.msg.clone() {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, bob);
-            assert_eq!(amount[0].amount, expected)
-        }
 
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    // un-pause the contract
+    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts).unwrap();
 
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::from(0u64));
+    // execute the same handler, should work
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    let info = mock_info(&owner, &[]);
+    execute(deps.as_mut(), mock_env(), info, reward_msg).unwrap();
 }
 
-/// Covers if the state/parameters storage is updated to the given value,
-/// who sends the message, and if
-/// RewardUpdateDenom message is sent to the reward contract
 #[test]
-pub fn test_update_params() {
+pub fn test_guardians() {
     let mut deps = dependencies(&[]);
 
     let _validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
-    // test with no swap denom.
-    let update_prams = UpdateParams {
-        epoch_period: Some(20),
-        unbonding_period: None,
-    };
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
 
+    let guardian1 = String::from("guardian1");
+    let guardian2 = String::from("guardian2");
+
     initialize(
         deps.borrow_mut(),
-        owner,
+        owner.clone(),
         reward_contract,
         statom_token_contract,
     );
 
-    let invalid_info = mock_info(String::from("invalid").as_str(), &[]);
-    let res = execute(
-        deps.as_mut(),
-        mock_env(),
-        invalid_info,
-        update_prams.clone(),
+    // try to add guardians
+    // must fail, because only the owner can add guardians
+    let add_guardians = ExecuteMsg::AddGuardians {
+        addresses: vec![guardian1.clone(), guardian2.clone()],
+    };
+    let creator_info = mock_info(String::from("some_user").as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, add_guardians);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // add guardians
+    let add_guardians = ExecuteMsg::AddGuardians {
+        addresses: vec![guardian1.clone(), guardian2.clone()],
+    };
+    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), creator_info, add_guardians).unwrap();
+
+    let query_guardians = QueryMsg::Guardians {};
+    let guardians: Vec<String> =
+        from_binary(&query(deps.as_ref(), mock_env(), query_guardians).unwrap()).unwrap();
+    assert_eq!(guardians.len(), 2);
+    assert_eq!(guardians, vec![guardian1.clone(), guardian2.clone()]);
+
+    // set paused = true
+    let pause_contracts = ExecuteMsg::PauseContracts { reason: None };
+    let guardian_info = mock_info(guardian1.as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts).unwrap();
+
+    // try to run a not allowed action, should return an error
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    let info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the contract is temporarily paused")
     );
+
+    // guardians cannot unpause the contracts
+    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
+    let creator_info = mock_info(guardian2.as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // but the owner can
+    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
+    let creator_info = mock_info(owner.as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts).unwrap();
+
+    // check that contracts are unpaused
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    let info = mock_info(&owner, &[]);
+    execute(deps.as_mut(), mock_env(), info, reward_msg).unwrap();
+
+    // try to remove guardian
+    // must fail, because only the owner can remove guardians
+    let remove_guardian = ExecuteMsg::RemoveGuardians {
+        addresses: vec![guardian1.clone()],
+    };
+    let creator_info = mock_info(guardian2.as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, remove_guardian);
     assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // remove guardian
+    let remove_guardian = ExecuteMsg::RemoveGuardians {
+        addresses: vec![guardian1.clone()],
+    };
     let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    execute(deps.as_mut(), mock_env(), creator_info, remove_guardian).unwrap();
 
-    let params: Parameters =
-        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
-    assert_eq!(params.epoch_period, 20);
-    assert_eq!(params.underlying_coin_denom, "uatom");
-    assert_eq!(params.unbonding_period, 2);
+    let query_guardians = QueryMsg::Guardians {};
+    let guardians: Vec<String> =
+        from_binary(&query(deps.as_ref(), mock_env(), query_guardians).unwrap()).unwrap();
+    assert_eq!(guardians.len(), 1);
+    assert_eq!(guardians, vec![guardian2.clone()]);
 
-    // test with some swap_denom.
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: Some(3),
-    };
+    // removed guardian cannot pause the contracts
+    let pause_contracts = ExecuteMsg::PauseContracts { reason: None };
+    let guardian_info = mock_info(guardian1.as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
 
-    // the result must be 1
-    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    // but the rest can
+    let pause_contracts = ExecuteMsg::PauseContracts { reason: None };
+    let guardian_info = mock_info(guardian2.as_str(), &[]);
+    execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts).unwrap();
 
-    let params: Parameters =
-        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
-    assert_eq!(params.epoch_period, 20);
-    assert_eq!(params.underlying_coin_denom, "uatom");
-    assert_eq!(params.unbonding_period, 3);
+    // try to run a not allowed action, should return an error
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    let info = mock_info(&owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the contract is temporarily paused")
+    );
 }
 
-/// Covers if the storage affected by update_config are updated properly
 #[test]
-pub fn proper_update_config() {
+pub fn test_roles() {
     let mut deps = dependencies(&[]);
 
     let _validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
     let owner = String::from("owner1");
-    let new_owner = String::from("new_owner");
-    let invalid_owner = String::from("invalid_owner");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
 
     initialize(
         deps.borrow_mut(),
         owner.clone(),
-        reward_contract.clone(),
-        statom_token_contract.clone(),
+        reward_contract,
+        statom_token_contract,
     );
 
-    let config = Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    let pauser = mock_info("pauser", &[]);
+    let unpauser = mock_info("unpauser", &[]);
+    let params_admin = mock_info("params_admin", &[]);
+    for (role, info) in [
+        (Role::Pauser, &pauser),
+        (Role::Unpauser, &unpauser),
+        (Role::ParamsAdmin, &params_admin),
+    ]
+    .iter()
+    {
+        let grant_role = ExecuteMsg::GrantRole {
+            role: *role,
+            address: info.sender.to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            grant_role,
+        )
+        .unwrap();
+    }
 
-    // make sure the other configs are still the same.
-    assert_eq!(
-        &config_query.reward_dispatcher_contract.unwrap(),
-        &reward_contract
-    );
-    assert_eq!(&config_query.owner, &owner);
+    let roles: RolesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap()).unwrap();
+    let pausers = roles.roles.iter().find(|r| r.role == Role::Pauser).unwrap();
+    assert_eq!(pausers.addresses, vec![String::from("pauser")]);
 
-    // only the owner can call this message
-    let update_config = UpdateConfig {
-        owner: Some(new_owner.clone()),
-        rewards_dispatcher_contract: None,
-        validators_registry_contract: None,
-        statom_token_contract: None,
-    };
-    let info = mock_info(&invalid_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config);
+    // the pauser can't unpause and the unpauser can't pause
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        unpauser.clone(),
+        ExecuteMsg::PauseContracts { reason: None },
+    );
     assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        pauser.clone(),
+        ExecuteMsg::PauseContracts { reason: None },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        pauser.clone(),
+        ExecuteMsg::UnpauseContracts {},
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        unpauser,
+        ExecuteMsg::UnpauseContracts {},
+    )
+    .unwrap();
 
-    // change the owner
-    let update_config = UpdateConfig {
-        owner: Some(new_owner.clone()),
-        rewards_dispatcher_contract: None,
-        validators_registry_contract: None,
-        statom_token_contract: None,
-    };
-    let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let config = CONFIG.load(&deps.storage).unwrap();
-    let new_owner_raw = deps.api.addr_validate(&new_owner).unwrap();
-    assert_eq!(new_owner_raw, config.creator);
-
-    // new owner can send the owner related messages
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: None,
-    };
-
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    // previous owner cannot send this message
-    let update_prams = UpdateParams {
-        epoch_period: None,
+    // the params admin can update the params but not the config
+    let update_params = UpdateParams {
+        epoch_period: Some(20),
         unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: None,
+        guardian_pause_cooldown: None,
     };
-
-    let new_owner_info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams);
+    let res = execute(deps.as_mut(), mock_env(), pauser, update_params.clone());
     assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        params_admin.clone(),
+        update_params,
+    )
+    .unwrap();
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
 
     let update_config = UpdateConfig {
-        owner: None,
-        rewards_dispatcher_contract: Some(String::from("new reward")),
-        validators_registry_contract: None,
-        statom_token_contract: None,
-    };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 1);
-
-    let msg: CosmosMsg = CosmosMsg::Distribution(DistributionMsg::SetWithdrawAddress {
-        address: String::from("new reward"),
-    });
-    assert_eq!(msg, res.messages[0].msg.clone());
-
-    let config = Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
-    assert_eq!(
-        config_query.reward_dispatcher_contract.unwrap(),
-        String::from("new reward")
-    );
-
-    let config = Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
-
-    // make sure the other configs are still the same.
-    assert_eq!(
-        config_query.reward_dispatcher_contract.unwrap(),
-        String::from("new reward")
-    );
-    assert_eq!(config_query.owner, new_owner);
-
-    let update_config = UpdateConfig {
-        owner: None,
-        rewards_dispatcher_contract: None,
+        rewards_dispatcher_contract: Some(String::from("new_reward")),
         validators_registry_contract: None,
         statom_token_contract: None,
+        claims_nft_contract: None,
     };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let update_config = UpdateConfig {
-        owner: None,
-        rewards_dispatcher_contract: None,
-        validators_registry_contract: Some(String::from("new registry")),
-        statom_token_contract: None,
-    };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let config = Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
-    assert_eq!(
-        config_query.validators_registry_contract.unwrap(),
-        String::from("new registry"),
-    );
-
-    let update_config = UpdateConfig {
-        owner: None,
-        rewards_dispatcher_contract: None,
-        validators_registry_contract: None,
-        statom_token_contract: Some(statom_token_contract.clone()),
-    };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config);
-    assert_eq!(
-        res.unwrap_err(),
-        StdError::generic_err("updating stAtom token address is forbidden",)
-    );
-
-    let config = Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
-    assert_eq!(
-        config_query.statom_token_contract.unwrap(),
-        statom_token_contract,
-    );
-}
-
-fn set_delegation(querier: &mut WasmMockQuerier, validator: Validator, amount: u128, denom: &str) {
-    querier.update_staking(
-        "uatom",
-        &[validator.clone()],
-        &[sample_delegation(validator.address, coin(amount, denom))],
-    );
-}
-
-fn set_delegation_query(
-    querier: &mut WasmMockQuerier,
-    delegate: &[FullDelegation],
-    validators: &[Validator],
-) {
-    querier.update_staking("uatom", validators, delegate);
-}
-
-fn sample_delegation(addr: String, amount: Coin) -> FullDelegation {
-    let can_redelegate = amount.clone();
-    let accumulated_rewards = coins(0, &amount.denom);
-    FullDelegation {
-        validator: addr,
-        delegator: Addr::unchecked(String::from(MOCK_CONTRACT_ADDR)),
-        amount,
-        can_redelegate,
-        accumulated_rewards,
-    }
-}
-
-// sample MIR claim msg
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-#[allow(clippy::upper_case_acronyms)]
-pub enum MIRMsg {
-    MIRClaim {},
+    let res = execute(deps.as_mut(), mock_env(), params_admin, update_config);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
 }
 
 #[test]
-fn proper_redelegate_proxy() {
+pub fn test_pause_operations() {
     let mut deps = dependencies(&[]);
 
+    let validator = sample_validator(DEFAULT_VALIDATOR);
     set_validator_mock(&mut deps.querier);
 
-    let addr1 = String::from("addr1000");
-
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
-    let validators_registry = String::from("validators_registry");
+    let guardian = String::from("guardian");
 
     initialize(
         deps.borrow_mut(),
@@ -2392,68 +4922,121 @@ fn proper_redelegate_proxy() {
         statom_token_contract,
     );
 
-    let redelegate_proxy_msg = ExecuteMsg::RedelegateProxy {
-        src_validator: String::from("src_validator"),
-        redelegations: vec![(String::from("dst_validator"), Coin::new(100, "uatom"))],
-    };
+    let add_guardians = ExecuteMsg::AddGuardians {
+        addresses: vec![guardian.clone()],
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        add_guardians,
+    )
+    .unwrap();
+
+    // a guardian stops new bonds only
+    let pause_bond = ExecuteMsg::PauseOperations {
+        operations: vec![PausableOperation::Bond],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("some_user", &[]),
+        pause_bond.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&guardian, &[]),
+        pause_bond,
+    )
+    .unwrap();
+    // the pause of a guardian expires by default
+    let expires_at = mock_env().block.time.seconds() + 3 * 24 * 60 * 60;
+    assert!(res
+        .attributes
+        .contains(&attr("expires_at", expires_at.to_string())));
+
+    let pause_state: PauseStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseState {}).unwrap()).unwrap();
+    assert_eq!(
+        pause_state,
+        PauseStateResponse {
+            paused: false,
+            bond: true,
+            unbond: false,
+            withdraw: false,
+            rewards: false,
+            token_transfers: false,
+        }
+    );
+
+    let bond = ExecuteMsg::BondForStAtom {};
+    let info = mock_info("bob", &[coin(10, "uatom")]);
+    let res = execute(deps.as_mut(), mock_env(), info, bond);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the contract is temporarily paused")
+    );
 
-    // invalid sender
-    let info = mock_info(&addr1, &[]);
-    let res = execute(
+    // the other operations keep working
+    let reward_msg = ExecuteMsg::DispatchRewards {};
+    execute(
         deps.as_mut(),
         mock_env(),
-        info,
-        redelegate_proxy_msg.clone(),
+        mock_info(&owner, &[]),
+        reward_msg,
     )
-    .unwrap_err();
-    assert_eq!(res, StdError::generic_err("unauthorized"));
+    .unwrap();
 
-    // check that validators_registry can send such messages
-    let info = mock_info(&validators_registry, &[]);
+    // the guardian can't unpause the operation, the owner can
+    let unpause_bond = ExecuteMsg::UnpauseOperations {
+        operations: vec![PausableOperation::Bond],
+    };
     let res = execute(
         deps.as_mut(),
         mock_env(),
-        info,
-        redelegate_proxy_msg.clone(),
+        mock_info(&guardian, &[]),
+        unpause_bond.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // pausing the contracts pauses every operation and unpausing them keeps the bond paused
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::PauseContracts { reason: None },
     )
     .unwrap();
+    let pause_state: PauseStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseState {}).unwrap()).unwrap();
+    assert!(pause_state.paused && pause_state.withdraw && pause_state.token_transfers);
 
-    let redelegate = &res.messages[0];
-    match redelegate.msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Redelegate {
-            src_validator,
-            dst_validator,
-            amount,
-        }) => {
-            assert_eq!(src_validator, String::from("src_validator"));
-            assert_eq!(dst_validator, String::from("dst_validator"));
-            assert_eq!(amount, Coin::new(100, "uatom"));
-        }
-        _ => panic!("Unexpected message: {:?}", redelegate),
-    }
-
-    // check that creator can send such messages
-    let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, redelegate_proxy_msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::UnpauseContracts {},
+    )
+    .unwrap();
+    let pause_state: PauseStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseState {}).unwrap()).unwrap();
+    assert!(!pause_state.paused && pause_state.bond && !pause_state.withdraw);
 
-    let redelegate = &res.messages[0];
-    match redelegate.msg.clone() {
-        CosmosMsg::Staking(StakingMsg::Redelegate {
-            src_validator,
-            dst_validator,
-            amount,
-        }) => {
-            assert_eq!(src_validator, String::from("src_validator"));
-            assert_eq!(dst_validator, String::from("dst_validator"));
-            assert_eq!(amount, Coin::new(100, "uatom"));
-        }
-        _ => panic!("Unexpected message: {:?}", redelegate),
-    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        unpause_bond,
+    )
+    .unwrap();
+    do_register_validator(&mut deps, validator);
+    do_bond_statom(&mut deps, String::from("bob"), Uint128::from(10u64));
 }
 
-///
 #[test]
-pub fn test_pause() {
+pub fn test_guardian_pause_expiry() {
     let mut deps = dependencies(&[]);
 
     let _validator = sample_validator(DEFAULT_VALIDATOR);
@@ -2462,6 +5045,7 @@ pub fn test_pause() {
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
+    let guardian = String::from("guardian");
 
     initialize(
         deps.borrow_mut(),
@@ -2470,33 +5054,148 @@ pub fn test_pause() {
         statom_token_contract,
     );
 
-    // set paused = true
-    let pause_contracts = ExecuteMsg::PauseContracts {};
-    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), creator_info, pause_contracts).unwrap();
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: Some(100),
+        guardian_pause_cooldown: Some(50),
+    };
+    let owner_info = mock_info(&owner, &[]);
+    execute(deps.as_mut(), mock_env(), owner_info.clone(), update_params).unwrap();
+    let add_guardians = ExecuteMsg::AddGuardians {
+        addresses: vec![guardian.clone()],
+    };
+    execute(deps.as_mut(), mock_env(), owner_info.clone(), add_guardians).unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let pause_contracts = ExecuteMsg::PauseContracts {
+        reason: Some(String::from("incident")),
+    };
+    let guardian_info = mock_info(&guardian, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        guardian_info.clone(),
+        pause_contracts.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause_contracts"),
+            attr("initiator", guardian.clone()),
+            attr("expires_at", (now + 100).to_string()),
+        ]
+    );
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(params.paused, Some(true));
+    assert_eq!(
+        params.pause_info,
+        Some(PauseInfo {
+            initiator: Addr::unchecked(guardian.clone()),
+            reason: Some(String::from("incident")),
+            paused_at: now,
+            expires_at: Some(now + 100),
+        })
+    );
 
-    // try to run a not allowed action, should return an error
     let reward_msg = ExecuteMsg::DispatchRewards {};
-    let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        owner_info.clone(),
+        reward_msg.clone(),
+    );
     assert_eq!(
         res.unwrap_err(),
         StdError::generic_err("the contract is temporarily paused")
     );
 
-    // un-pause the contract
-    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
-    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts).unwrap();
+    // the contracts resume once the pause has expired
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        reward_msg.clone(),
+    )
+    .unwrap();
+    let params: Parameters = from_binary(&query(deps.as_ref(), env, Params {}).unwrap()).unwrap();
+    assert_eq!(params.paused, Some(false));
+    assert_eq!(params.pause_info, None);
 
-    // execute the same handler, should work
-    let reward_msg = ExecuteMsg::DispatchRewards {};
-    let info = mock_info(&owner, &[]);
-    execute(deps.as_mut(), mock_env(), info, reward_msg).unwrap();
+    // the guardian can't pause again until the cooldown has passed
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(120);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        guardian_info.clone(),
+        pause_contracts.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(format!(
+            "the contracts can't be paused by {} again before {}",
+            guardian,
+            now + 150
+        ))
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info.clone(),
+        pause_contracts,
+    )
+    .unwrap();
+
+    // only the owner can extend the pause
+    let extend_pause = ExecuteMsg::ExtendPause { expires_at: None };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info,
+        extend_pause.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(deps.as_mut(), env.clone(), owner_info.clone(), extend_pause).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(1000);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        reward_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("the contract is temporarily paused")
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        ExecuteMsg::UnpauseContracts {},
+    )
+    .unwrap();
+    execute(deps.as_mut(), env, owner_info, reward_msg).unwrap();
 }
 
 #[test]
-pub fn test_guardians() {
+pub fn test_guardian_operation_pause_expiry() {
     let mut deps = dependencies(&[]);
 
     let _validator = sample_validator(DEFAULT_VALIDATOR);
@@ -2505,9 +5204,7 @@ pub fn test_guardians() {
     let owner = String::from("owner1");
     let statom_token_contract = String::from("statom_token");
     let reward_contract = String::from("reward");
-
-    let guardian1 = String::from("guardian1");
-    let guardian2 = String::from("guardian2");
+    let guardian = String::from("guardian");
 
     initialize(
         deps.borrow_mut(),
@@ -2516,97 +5213,102 @@ pub fn test_guardians() {
         statom_token_contract,
     );
 
-    // try to add guardians
-    // must fail, because only the owner can add guardians
-    let add_guardians = ExecuteMsg::AddGuardians {
-        addresses: vec![guardian1.clone(), guardian2.clone()],
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        liquidity_buffer_target: None,
+        instant_unbond_fee: None,
+        max_insurance_cover: None,
+        min_bond_amount: None,
+        max_exchange_rate_increase: None,
+        timelock_period: None,
+        max_guardian_pause_duration: Some(100),
+        guardian_pause_cooldown: Some(50),
     };
-    let creator_info = mock_info(String::from("some_user").as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, add_guardians);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
-
-    // add guardians
+    let owner_info = mock_info(&owner, &[]);
+    execute(deps.as_mut(), mock_env(), owner_info.clone(), update_params).unwrap();
     let add_guardians = ExecuteMsg::AddGuardians {
-        addresses: vec![guardian1.clone(), guardian2.clone()],
+        addresses: vec![guardian.clone()],
     };
-    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), creator_info, add_guardians).unwrap();
+    execute(deps.as_mut(), mock_env(), owner_info.clone(), add_guardians).unwrap();
 
-    let query_guardians = QueryMsg::Guardians {};
-    let guardians: Vec<String> =
-        from_binary(&query(deps.as_ref(), mock_env(), query_guardians).unwrap()).unwrap();
-    assert_eq!(guardians.len(), 2);
-    assert_eq!(guardians, vec![guardian1.clone(), guardian2.clone()]);
+    // the pause of the guardian expires, the one of the owner doesn't
+    let now = mock_env().block.time.seconds();
+    let guardian_info = mock_info(&guardian, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        guardian_info.clone(),
+        ExecuteMsg::PauseOperations {
+            operations: vec![PausableOperation::Rewards],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause_operations"),
+            attr(
+                "paused_operations",
+                PausableOperation::Rewards.mask().to_string()
+            ),
+            attr("expires_at", (now + 100).to_string()),
+        ]
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        owner_info.clone(),
+        ExecuteMsg::PauseOperations {
+            operations: vec![PausableOperation::Bond],
+        },
+    )
+    .unwrap();
 
-    // set paused = true
-    let pause_contracts = ExecuteMsg::PauseContracts {};
-    let guardian_info = mock_info(guardian1.as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts).unwrap();
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), Params {}).unwrap()).unwrap();
+    assert_eq!(
+        params.operation_pauses,
+        vec![OperationPause {
+            operation: PausableOperation::Rewards,
+            expires_at: now + 100,
+        }]
+    );
 
-    // try to run a not allowed action, should return an error
     let reward_msg = ExecuteMsg::DispatchRewards {};
-    let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        owner_info.clone(),
+        reward_msg.clone(),
+    );
     assert_eq!(
         res.unwrap_err(),
         StdError::generic_err("the contract is temporarily paused")
     );
 
-    // guardians cannot unpause the contracts
-    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
-    let creator_info = mock_info(guardian2.as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
-
-    // but the owner can
-    let unpause_contracts = ExecuteMsg::UnpauseContracts {};
-    let creator_info = mock_info(owner.as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), creator_info, unpause_contracts).unwrap();
-
-    // check that contracts are unpaused
-    let reward_msg = ExecuteMsg::DispatchRewards {};
-    let info = mock_info(&owner, &[]);
-    execute(deps.as_mut(), mock_env(), info, reward_msg).unwrap();
-
-    // try to remove guardian
-    // must fail, because only the owner can remove guardians
-    let remove_guardian = ExecuteMsg::RemoveGuardians {
-        addresses: vec![guardian1.clone()],
-    };
-    let creator_info = mock_info(guardian2.as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, remove_guardian);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
-
-    // remove guardian
-    let remove_guardian = ExecuteMsg::RemoveGuardians {
-        addresses: vec![guardian1.clone()],
-    };
-    let creator_info = mock_info(String::from("owner1").as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), creator_info, remove_guardian).unwrap();
-
-    let query_guardians = QueryMsg::Guardians {};
-    let guardians: Vec<String> =
-        from_binary(&query(deps.as_ref(), mock_env(), query_guardians).unwrap()).unwrap();
-    assert_eq!(guardians.len(), 1);
-    assert_eq!(guardians, vec![guardian2.clone()]);
-
-    // removed guardian cannot pause the contracts
-    let pause_contracts = ExecuteMsg::PauseContracts {};
-    let guardian_info = mock_info(guardian1.as_str(), &[]);
-    let res = execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
-
-    // but the rest can
-    let pause_contracts = ExecuteMsg::PauseContracts {};
-    let guardian_info = mock_info(guardian2.as_str(), &[]);
-    execute(deps.as_mut(), mock_env(), guardian_info, pause_contracts).unwrap();
-
-    // try to run a not allowed action, should return an error
-    let reward_msg = ExecuteMsg::DispatchRewards {};
-    let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, reward_msg);
+    // the operation pause shares the cooldown of the pause of the contracts
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(120);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        guardian_info,
+        ExecuteMsg::PauseContracts { reason: None },
+    );
     assert_eq!(
         res.unwrap_err(),
-        StdError::generic_err("the contract is temporarily paused")
+        StdError::generic_err(format!(
+            "the contracts can't be paused by {} again before {}",
+            guardian,
+            now + 150
+        ))
     );
+
+    execute(deps.as_mut(), env.clone(), owner_info, reward_msg).unwrap();
+    let pause_state: PauseStateResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PauseState {}).unwrap()).unwrap();
+    assert!(!pause_state.rewards && pause_state.bond);
+    let params: Parameters = from_binary(&query(deps.as_ref(), env, Params {}).unwrap()).unwrap();
+    assert!(params.operation_pauses.is_empty());
 }
@@ -12,12 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::contract::slashing;
+use crate::contract::{query_total_statom_issued, slashing};
 use crate::state::{
-    get_finished_amount, read_unbond_history, remove_unbond_wait_list, store_unbond_history,
-    store_unbond_wait_list, CONFIG, CURRENT_BATCH, PARAMETERS, STATE,
+    decrease_book_delegation, get_finished_amount, load_parameters, read_unbond_history,
+    remove_unbond_wait_list, store_unbond_history, store_unbond_wait_list, CLAIMS_COUNT, CONFIG,
+    CURRENT_BATCH, PARAMETERS, STATE, UNBOND_CLAIMS, WITHDRAW_OPT_OUTS,
+};
+use basset::hub::{
+    ClaimMintMsg, ClaimsNftExecuteMsg, CurrentBatch, PausableOperation, State, UnbondClaim,
+    UnbondHistory,
 };
-use basset::hub::{CurrentBatch, State, UnbondHistory};
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, coin, coins, to_binary, BankMsg, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response,
@@ -25,19 +29,26 @@ use cosmwasm_std::{
 };
 use cw20::Cw20ExecuteMsg;
 use lido_cosmos_validators_registry::common::calculate_undelegations;
-use lido_cosmos_validators_registry::registry::ValidatorResponse;
+use lido_cosmos_validators_registry::registry::{ValidatorResponse, DEFAULT_WEIGHT};
 use signed_integer::SignedInt;
 
 pub fn execute_withdraw_unbonded(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    recipient: Option<String>,
+    batch_ids: Option<Vec<u64>>,
+    limit: Option<u32>,
 ) -> StdResult<Response> {
-    let params = PARAMETERS.load(deps.storage)?;
-    if params.paused.unwrap_or(false) {
+    let params = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Withdraw) {
         return Err(StdError::generic_err("The contract is temporarily paused"));
     }
     let sender_human = info.sender;
+    let recipient = match recipient {
+        Some(r) => deps.api.addr_validate(&r)?,
+        None => sender_human.clone(),
+    };
     let contract_address = env.contract.address.clone();
     let unbonding_period = params.unbonding_period;
     let coin_denom = params.underlying_coin_denom;
@@ -51,10 +62,10 @@ pub fn execute_withdraw_unbonded(
         .amount;
 
     // calculate withdraw rate for user requests
-    process_withdraw_rate(&mut deps, historical_time, hub_balance)?;
+    let completed = process_withdraw_rate(&mut deps, historical_time, hub_balance, limit)?;
 
     let (withdraw_amount, deprecated_batches) =
-        get_finished_amount(deps.storage, sender_human.to_string())?;
+        get_finished_amount(deps.storage, sender_human.to_string(), batch_ids, limit)?;
 
     if withdraw_amount.is_zero() {
         return Err(StdError::generic_err(format!(
@@ -67,15 +78,11 @@ pub fn execute_withdraw_unbonded(
     remove_unbond_wait_list(deps.storage, deprecated_batches, sender_human.to_string())?;
 
     // Update previous balance used for calculation in next Atom batch release
-    let prev_balance = hub_balance.checked_sub(withdraw_amount)?;
-    STATE.update(deps.storage, |mut last_state| -> StdResult<_> {
-        last_state.prev_hub_balance = prev_balance;
-        Ok(last_state)
-    })?;
+    update_prev_hub_balance(deps.storage, completed, hub_balance, withdraw_amount)?;
 
     // Send the money to the user
     let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
-        to_address: sender_human.to_string(),
+        to_address: recipient.to_string(),
         amount: coins(withdraw_amount.u128(), &*coin_denom),
     }
     .into()];
@@ -83,15 +90,211 @@ pub fn execute_withdraw_unbonded(
     let res = Response::new().add_messages(msgs).add_attributes(vec![
         attr("action", "finish_burn"),
         attr("from", contract_address),
+        attr("recipient", recipient),
+        attr("amount", withdraw_amount),
+    ]);
+    Ok(res)
+}
+
+/// Permissionless, sends back unbonded coin to each of the addresses.
+/// The addresses which opted out of it are skipped
+pub fn execute_withdraw_unbonded_for(
+    mut deps: DepsMut,
+    env: Env,
+    addresses: Vec<String>,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let params = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Withdraw) {
+        return Err(StdError::generic_err("The contract is temporarily paused"));
+    }
+    let coin_denom = params.underlying_coin_denom;
+
+    let historical_time = env.block.time.seconds() - params.unbonding_period;
+
+    // query hub balance for process withdraw rate.
+    let hub_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &*coin_denom)?
+        .amount;
+
+    // calculate withdraw rate for user requests
+    let completed = process_withdraw_rate(&mut deps, historical_time, hub_balance, limit)?;
+
+    let mut total_withdraw_amount = Uint128::zero();
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut attrs = vec![attr("action", "withdraw_unbonded_for")];
+    for address in addresses {
+        let address = deps.api.addr_validate(&address)?.to_string();
+        if WITHDRAW_OPT_OUTS.has(deps.storage, address.clone()) {
+            attrs.push(attr("opted_out", address));
+            continue;
+        }
+
+        let (withdraw_amount, deprecated_batches) =
+            get_finished_amount(deps.storage, address.clone(), None, limit)?;
+        if withdraw_amount.is_zero() {
+            continue;
+        }
+
+        // remove the previous batches for the user
+        remove_unbond_wait_list(deps.storage, deprecated_batches, address.clone())?;
+        total_withdraw_amount += withdraw_amount;
+
+        msgs.push(
+            BankMsg::Send {
+                to_address: address.clone(),
+                amount: coins(withdraw_amount.u128(), &*coin_denom),
+            }
+            .into(),
+        );
+        attrs.push(attr(
+            "withdrawn",
+            format!("{}:{}", address, withdraw_amount),
+        ));
+    }
+
+    if total_withdraw_amount.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "No withdrawable {} assets are available yet",
+            coin_denom
+        )));
+    }
+
+    // Update previous balance used for calculation in next Atom batch release
+    update_prev_hub_balance(deps.storage, completed, hub_balance, total_withdraw_amount)?;
+
+    attrs.push(attr("amount", total_withdraw_amount));
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+
+/// Opts the sender out of (or back in to) withdrawals made on their behalf
+pub fn execute_set_withdraw_opt_out(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    opt_out: bool,
+) -> StdResult<Response> {
+    if opt_out {
+        WITHDRAW_OPT_OUTS.save(deps.storage, info.sender.to_string(), &true)?;
+    } else {
+        WITHDRAW_OPT_OUTS.remove(deps.storage, info.sender.to_string());
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_withdraw_opt_out"),
+        attr("address", info.sender),
+        attr("opt_out", opt_out.to_string()),
+    ]))
+}
+
+/// Pays out the released unbond claim received from the claims contract and burns the claim
+pub(crate) fn execute_withdraw_claim(
+    deps: DepsMut,
+    token_id: String,
+    owner: String,
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let recipient = match recipient {
+        Some(r) => deps.api.addr_validate(&r)?,
+        None => deps.api.addr_validate(&owner)?,
+    };
+    let coin_denom = params.underlying_coin_denom;
+
+    let claim = UNBOND_CLAIMS
+        .may_load(deps.storage, &token_id)?
+        .ok_or_else(|| StdError::generic_err(format!("Unbond claim {} is not found", token_id)))?;
+
+    // the batches are released by ProcessWithdrawRate, which bounds the work per call
+    let withdraw_amount = match read_unbond_history(deps.storage, claim.batch_id) {
+        Ok(h) if h.released => claim.statom_amount * h.statom_withdraw_rate,
+        _ => {
+            return Err(StdError::generic_err(format!(
+                "Unbond claim {} is not released yet, release its batch with ProcessWithdrawRate",
+                token_id
+            )))
+        }
+    };
+
+    UNBOND_CLAIMS.remove(deps.storage, &token_id);
+
+    // Update previous balance used for calculation in next Atom batch release
+    STATE.update(deps.storage, |mut last_state| -> StdResult<_> {
+        last_state.prev_hub_balance = last_state.prev_hub_balance.checked_sub(withdraw_amount)?;
+        Ok(last_state)
+    })?;
+
+    let claims_contract = config.claims_nft_contract.ok_or_else(|| {
+        StdError::generic_err("the claims nft contract must have been registered")
+    })?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: claims_contract.to_string(),
+        msg: to_binary(&ClaimsNftExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    })];
+    if !withdraw_amount.is_zero() {
+        msgs.push(
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins(withdraw_amount.u128(), &*coin_denom),
+            }
+            .into(),
+        );
+    }
+
+    let res = Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "withdraw_claim"),
+        attr("token_id", token_id),
+        attr("owner", owner),
+        attr("recipient", recipient),
         attr("amount", withdraw_amount),
     ]);
     Ok(res)
 }
 
+/// Updates the previous hub balance after the withdrawal. If some released batches are
+/// left for the next process_withdraw_rate call, the balance of their coins is kept out of it.
+fn update_prev_hub_balance(
+    storage: &mut dyn Storage,
+    completed: bool,
+    hub_balance: Uint128,
+    withdraw_amount: Uint128,
+) -> StdResult<()> {
+    STATE.update(storage, |mut last_state| -> StdResult<_> {
+        let balance = if completed {
+            hub_balance
+        } else {
+            last_state.prev_hub_balance
+        };
+        last_state.prev_hub_balance = balance.checked_sub(withdraw_amount)?;
+        Ok(last_state)
+    })?;
+    Ok(())
+}
+
+/// Returns the history of the batch if it is not released yet
+/// and its unbonding period has passed
+fn read_unreleased_history(
+    storage: &dyn Storage,
+    batch_id: u64,
+    historical_time: u64,
+) -> Option<UnbondHistory> {
+    match read_unbond_history(storage, batch_id) {
+        Ok(h) if h.time <= historical_time && !h.released => Some(h),
+        _ => None,
+    }
+}
+
 fn calculate_newly_added_unbonded_amount(
     storage: &mut dyn Storage,
     last_processed_batch: u64,
     historical_time: u64,
+    limit: Option<u32>,
 ) -> (Uint256, u64) {
     let mut statom_total_unbonded_amount = Uint256::zero();
     let mut batch_count: u64 = 0;
@@ -100,20 +303,15 @@ fn calculate_newly_added_unbonded_amount(
     // to calculate newly added unbonded amount
     let mut i = last_processed_batch + 1;
     loop {
-        let history: UnbondHistory;
-        match read_unbond_history(storage, i) {
-            Ok(h) => {
-                if h.time > historical_time {
-                    break;
-                }
-                if !h.released {
-                    history = h.clone();
-                } else {
-                    break;
-                }
+        if let Some(l) = limit {
+            if batch_count >= l as u64 {
+                break;
             }
-            Err(_) => break,
         }
+        let history = match read_unreleased_history(storage, i, historical_time) {
+            Some(h) => h,
+            None => break,
+        };
         let statom_burnt_amount = Uint256::from(history.statom_amount);
         let statom_historical_rate = Decimal256::from(history.statom_withdraw_rate);
         let statom_unbonded_amount = statom_burnt_amount * statom_historical_rate;
@@ -126,6 +324,26 @@ fn calculate_newly_added_unbonded_amount(
     (statom_total_unbonded_amount, batch_count)
 }
 
+/// Return the underlying undelegated by the unreleased batches whose unbonding period has passed,
+/// going back over the batches still unbonding only
+fn unbonded_undelegated_amount(
+    storage: &dyn Storage,
+    unbonding_amount: Uint128,
+    historical_time: u64,
+) -> StdResult<Uint128> {
+    let current_batch = CURRENT_BATCH.load(storage)?;
+    let mut still_unbonding = Uint128::zero();
+    for batch_id in (1..current_batch.id).rev() {
+        match read_unbond_history(storage, batch_id) {
+            Ok(h) if h.time > historical_time && !h.released => {
+                still_unbonding += h.undelegated_amount()
+            }
+            _ => break,
+        }
+    }
+    Ok(unbonding_amount.saturating_sub(still_unbonding))
+}
+
 fn calculate_new_withdraw_rate(
     amount: Uint128,
     withdraw_rate: Decimal,
@@ -174,24 +392,74 @@ fn calculate_new_withdraw_rate(
 
 /// This is designed for an accurate unbonded amount calculation.
 /// Execute while processing withdraw_unbonded
+/// At most limit batches are released if it is set. Returns true if no batch
+/// with a passed unbonding period is left unreleased.
 fn process_withdraw_rate(
     deps: &mut DepsMut,
     historical_time: u64,
     hub_balance: Uint128,
-) -> StdResult<()> {
+    limit: Option<u32>,
+) -> StdResult<bool> {
     let mut state = STATE.load(deps.storage)?;
 
     let last_processed_batch = state.last_processed_batch;
 
-    let (statom_total_unbonded_amount, batch_count) =
-        calculate_newly_added_unbonded_amount(deps.storage, last_processed_batch, historical_time);
+    let (statom_total_unbonded_amount, batch_count) = calculate_newly_added_unbonded_amount(
+        deps.storage,
+        last_processed_batch,
+        historical_time,
+        limit,
+    );
 
     if batch_count < 1 {
-        return Ok(());
+        return Ok(true);
+    }
+
+    let completed = read_unreleased_history(
+        deps.storage,
+        last_processed_batch + batch_count + 1,
+        historical_time,
+    )
+    .is_none();
+
+    // the buffered part of the released batches has been on the hub balance since
+    // their submission, it's taken out of the previous balance to count as unbonded
+    let mut buffered_amount = Uint128::zero();
+    let mut undelegated_amount = Uint128::zero();
+    for batch_id in last_processed_batch + 1..=last_processed_batch + batch_count {
+        let history = read_unbond_history(deps.storage, batch_id)?;
+        state.prev_hub_balance = state
+            .prev_hub_balance
+            .checked_sub(history.buffered_amount)?;
+        buffered_amount += history.buffered_amount;
+        undelegated_amount += history.undelegated_amount();
     }
 
     let balance_change = SignedInt::from_subtraction(hub_balance, state.prev_hub_balance);
-    let actual_unbonded_amount = balance_change.0;
+    let actual_unbonded_amount = if completed {
+        state.prev_hub_balance = hub_balance;
+        balance_change.0
+    } else {
+        // the balance also holds the coins of the unbonded batches left for the next call,
+        // so these batches take their share of the coins which came back from the validators
+        let amount = if balance_change.1 {
+            Uint128::zero()
+        } else {
+            let unbonded_amount =
+                unbonded_undelegated_amount(deps.storage, state.unbonding_amount, historical_time)?
+                    .max(undelegated_amount);
+            let returned_amount = balance_change.0.saturating_sub(buffered_amount);
+            if unbonded_amount.is_zero() {
+                buffered_amount
+            } else {
+                buffered_amount
+                    + returned_amount.multiply_ratio(undelegated_amount, unbonded_amount)
+            }
+        };
+        state.prev_hub_balance += amount;
+        amount
+    };
+    state.unbonding_amount = state.unbonding_amount.saturating_sub(undelegated_amount);
 
     let statom_slashed_amount = SignedInt::from_subtraction(
         statom_total_unbonded_amount,
@@ -199,24 +467,8 @@ fn process_withdraw_rate(
     );
 
     // Iterate again to calculate the withdraw rate for each unprocessed history
-    let mut iterator = last_processed_batch + 1;
-    loop {
-        let history: UnbondHistory;
-        match read_unbond_history(deps.storage, iterator) {
-            Ok(h) => {
-                if h.time > historical_time {
-                    break;
-                }
-                if !h.released {
-                    history = h
-                } else {
-                    break;
-                }
-            }
-            Err(_) => {
-                break;
-            }
-        }
+    for iterator in last_processed_batch + 1..=last_processed_batch + batch_count {
+        let history = read_unbond_history(deps.storage, iterator)?;
 
         // Calculate the new withdraw rate
         let statom_new_withdraw_rate = calculate_new_withdraw_rate(
@@ -232,15 +484,45 @@ fn process_withdraw_rate(
         history_for_i.released = true;
         store_unbond_history(deps.storage, iterator, history_for_i)?;
         state.last_processed_batch = iterator;
-        iterator += 1;
     }
 
     STATE.save(deps.storage, &state)?;
 
-    Ok(())
+    Ok(completed)
+}
+
+/// Releases the batches whose unbonding period has passed, at most limit batches if it is set.
+/// Permissionless, lets the release of many batches be split over several transactions
+pub fn execute_process_withdraw_rate(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let params = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Withdraw) {
+        return Err(StdError::generic_err("The contract is temporarily paused"));
+    }
+
+    let historical_time = env.block.time.seconds() - params.unbonding_period;
+    let hub_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &*params.underlying_coin_denom)?
+        .amount;
+
+    let completed = process_withdraw_rate(&mut deps, historical_time, hub_balance, limit)?;
+    let state = STATE.load(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "process_withdraw_rate"),
+        attr(
+            "last_processed_batch",
+            state.last_processed_batch.to_string(),
+        ),
+        attr("completed", completed.to_string()),
+    ]))
 }
 
-fn undelegate(deps: &DepsMut, claim: Uint128, delegator: String) -> StdResult<Vec<CosmosMsg>> {
+fn undelegate(deps: &mut DepsMut, claim: Uint128, delegator: String) -> StdResult<Vec<CosmosMsg>> {
     // read params
     let params = PARAMETERS.load(deps.storage)?;
     let coin_denom = params.underlying_coin_denom;
@@ -254,9 +536,11 @@ fn undelegate(deps: &DepsMut, claim: Uint128, delegator: String) -> StdResult<Ve
         .map(|d| ValidatorResponse {
             total_delegated: d.amount.amount,
             address: d.validator.clone(),
+            weight: DEFAULT_WEIGHT,
+            max_delegation: None,
         })
         .collect::<Vec<ValidatorResponse>>();
-    validators.sort_by(|v1, v2| v2.total_delegated.cmp(&v1.total_delegated));
+    validators.sort_by_key(|v| std::cmp::Reverse(v.total_delegated));
 
     let undelegations = calculate_undelegations(claim, validators.clone())?;
 
@@ -264,6 +548,11 @@ fn undelegate(deps: &DepsMut, claim: Uint128, delegator: String) -> StdResult<Ve
         if undelegated_amount.is_zero() {
             continue;
         }
+        decrease_book_delegation(
+            deps.storage,
+            &validators[index].address,
+            *undelegated_amount,
+        )?;
 
         let msgs: CosmosMsg = CosmosMsg::Staking(StakingMsg::Undelegate {
             validator: validators[index].address.clone(),
@@ -281,26 +570,62 @@ pub(crate) fn execute_unbond_statom(
     env: Env,
     amount: Uint128,
     sender: String,
+    recipient: Option<String>,
+    mint_claim: bool,
 ) -> StdResult<Response> {
+    // the unbond request is recorded for the recipient, who can withdraw it later
+    let recipient = match recipient {
+        Some(r) => deps.api.addr_validate(&r)?.to_string(),
+        None => sender.clone(),
+    };
+
     // Read params
     let params = PARAMETERS.load(deps.storage)?;
     let epoch_period = params.epoch_period;
+    let config = CONFIG.load(deps.storage)?;
 
     let mut current_batch = CURRENT_BATCH.load(deps.storage)?;
 
     // Check slashing, update state, and calculate the new exchange rate.
-    let mut state = slashing(&mut deps, env.clone())?;
+    let (mut state, mut messages) = slashing(&mut deps, env.clone())?;
 
     // Collect all the requests within a epoch period
     current_batch.requested_statom += amount;
 
-    store_unbond_wait_list(deps.storage, current_batch.id, sender.clone(), amount)?;
+    // If a claim is minted, the request is held by the claim token instead of the wait list
+    let claim_mint = if mint_claim {
+        let claims_contract = config.claims_nft_contract.clone().ok_or_else(|| {
+            StdError::generic_err("the claims nft contract must have been registered")
+        })?;
+        let claim_id = CLAIMS_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        CLAIMS_COUNT.save(deps.storage, &claim_id)?;
+
+        let token_id = claim_id.to_string();
+        let claim = UnbondClaim {
+            batch_id: current_batch.id,
+            statom_amount: amount,
+        };
+        UNBOND_CLAIMS.save(deps.storage, &token_id, &claim)?;
+
+        let mint_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: claims_contract.to_string(),
+            msg: to_binary(&ClaimsNftExecuteMsg::Mint(ClaimMintMsg {
+                token_id: token_id.clone(),
+                owner: recipient.clone(),
+                token_uri: None,
+                extension: claim,
+            }))?,
+            funds: vec![],
+        });
+        Some((token_id, mint_msg))
+    } else {
+        store_unbond_wait_list(deps.storage, current_batch.id, recipient.clone(), amount)?;
+        None
+    };
 
     let current_time = env.block.time.seconds();
     let passed_time = current_time - state.last_unbonded_time;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-
     // If the epoch period is passed, the undelegate message would be sent.
     if passed_time > epoch_period {
         let mut undelegate_msgs =
@@ -315,7 +640,6 @@ pub(crate) fn execute_unbond_statom(
     STATE.save(deps.storage, &state)?;
 
     // Send Burn message to token contract
-    let config = CONFIG.load(deps.storage)?;
     let token_address = config
         .statom_token_contract
         .ok_or_else(|| StdError::generic_err("the token contract must have been registered"))?;
@@ -327,15 +651,139 @@ pub(crate) fn execute_unbond_statom(
         funds: vec![],
     }));
 
-    let res = Response::new().add_messages(messages).add_attributes(vec![
+    let mut attrs = vec![
         attr("action", "burn"),
         attr("from", sender),
+        attr("recipient", recipient),
         attr("burnt_amount", amount),
         attr("unbonded_amount", amount),
+    ];
+    if let Some((token_id, mint_msg)) = claim_mint {
+        messages.push(mint_msg);
+        attrs.push(attr("claim_token_id", token_id));
+    }
+
+    let res = Response::new().add_messages(messages).add_attributes(attrs);
+    Ok(res)
+}
+
+/// This message must be call by receive_cw20
+/// This message will pay out coins from the liquidity buffer and burn stAtom tokens
+pub(crate) fn execute_instant_unbond_statom(
+    mut deps: DepsMut,
+    env: Env,
+    amount: Uint128,
+    sender: String,
+) -> StdResult<Response> {
+    // Read params
+    let params = PARAMETERS.load(deps.storage)?;
+    let coin_denom = params.underlying_coin_denom;
+
+    // Check slashing, update state, and calculate the new exchange rate.
+    let (mut state, mut messages) = slashing(&mut deps, env)?;
+
+    let payout = amount * state.statom_exchange_rate;
+    let fee_amount = payout * params.instant_unbond_fee;
+    let withdraw_amount = payout.checked_sub(fee_amount)?;
+
+    if withdraw_amount.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Invalid zero amount of {} to withdraw",
+            coin_denom
+        )));
+    }
+    if withdraw_amount > state.liquidity_buffer {
+        return Err(StdError::generic_err(format!(
+            "Not enough {} in the liquidity buffer: {} requested, {} available",
+            coin_denom, withdraw_amount, state.liquidity_buffer
+        )));
+    }
+
+    // the fee stays in the buffer and is shared by the remaining holders
+    state.liquidity_buffer = state.liquidity_buffer.checked_sub(withdraw_amount)?;
+    state.prev_hub_balance = state.prev_hub_balance.checked_sub(withdraw_amount)?;
+
+    // the tokens are burnt below, so they must not be counted in the supply anymore
+    let current_batch = CURRENT_BATCH.load(deps.storage)?;
+    let total_issued = query_total_statom_issued(deps.as_ref())?.checked_sub(amount)?;
+    state.update_statom_exchange_rate(total_issued, current_batch.requested_statom);
+
+    STATE.save(deps.storage, &state)?;
+
+    // Send Burn message to token contract
+    let config = CONFIG.load(deps.storage)?;
+    let token_address = config
+        .statom_token_contract
+        .ok_or_else(|| StdError::generic_err("the token contract must have been registered"))?;
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token_address.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+    messages.push(
+        BankMsg::Send {
+            to_address: sender.clone(),
+            amount: coins(withdraw_amount.u128(), &*coin_denom),
+        }
+        .into(),
+    );
+
+    let res = Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "instant_unbond"),
+        attr("from", sender),
+        attr("burnt_amount", amount),
+        attr("fee_amount", fee_amount),
+        attr("withdrawn", withdraw_amount),
     ]);
     Ok(res)
 }
 
+/// Permissionless, undelegates the requests of the current batch once the epoch period
+/// has passed, without waiting for the next unbond request
+pub fn execute_submit_batch(mut deps: DepsMut, env: Env) -> StdResult<Response> {
+    let params = load_parameters(deps.storage, &env)?;
+    if params.is_paused(PausableOperation::Unbond) {
+        return Err(StdError::generic_err("The contract is temporarily paused"));
+    }
+
+    let mut current_batch = CURRENT_BATCH.load(deps.storage)?;
+    if current_batch.requested_statom.is_zero() {
+        return Err(StdError::generic_err(
+            "The current batch has no unbond requests",
+        ));
+    }
+
+    // Check slashing, update state, and calculate the new exchange rate.
+    let (mut state, mut messages) = slashing(&mut deps, env.clone())?;
+
+    let passed_time = env.block.time.seconds() - state.last_unbonded_time;
+    if passed_time <= params.epoch_period {
+        return Err(StdError::generic_err(format!(
+            "The epoch period has not passed yet, {} seconds left",
+            params.epoch_period + 1 - passed_time
+        )));
+    }
+
+    let batch_id = current_batch.id;
+    let requested_statom = current_batch.requested_statom;
+    messages.append(&mut process_undelegations(
+        &mut deps,
+        env,
+        &mut current_batch,
+        &mut state,
+    )?);
+
+    CURRENT_BATCH.save(deps.storage, &current_batch)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "submit_batch"),
+        attr("batch_id", batch_id.to_string()),
+        attr("statom_amount", requested_statom),
+    ]))
+}
+
 fn process_undelegations(
     deps: &mut DepsMut,
     env: Env,
@@ -343,15 +791,31 @@ fn process_undelegations(
     state: &mut State,
 ) -> StdResult<Vec<CosmosMsg>> {
     // Apply the current exchange rate.
-    let statom_undelegation_amount = current_batch.requested_statom * state.statom_exchange_rate;
+    let statom_unbond_amount = current_batch.requested_statom * state.statom_exchange_rate;
+
+    // the exchange rate counts the liquidity buffer, so its share of the batch is paid
+    // from the buffer and only the rest is undelegated
+    let total_underlying = state.total_bond_statom_amount + state.liquidity_buffer;
+    let buffered_amount = if total_underlying.is_zero() {
+        Uint128::zero()
+    } else {
+        statom_unbond_amount.multiply_ratio(state.liquidity_buffer, total_underlying)
+    };
+    let statom_undelegation_amount = statom_unbond_amount.checked_sub(buffered_amount)?;
     let delegator = env.contract.address;
 
     // Send undelegated requests to possibly more than one validators
-    let undelegated_msgs = undelegate(deps, statom_undelegation_amount, delegator.to_string())?;
+    let undelegated_msgs = if statom_undelegation_amount.is_zero() {
+        vec![]
+    } else {
+        undelegate(deps, statom_undelegation_amount, delegator.to_string())?
+    };
 
+    state.liquidity_buffer = state.liquidity_buffer.checked_sub(buffered_amount)?;
     state.total_bond_statom_amount = state
         .total_bond_statom_amount
         .checked_sub(statom_undelegation_amount)?;
+    state.unbonding_amount += statom_undelegation_amount;
 
     // Store history for withdraw unbonded
     let history = UnbondHistory {
@@ -362,6 +826,7 @@ fn process_undelegations(
         statom_withdraw_rate: state.statom_exchange_rate,
 
         released: false,
+        buffered_amount,
     };
 
     store_unbond_history(deps.storage, current_batch.id, history)?;
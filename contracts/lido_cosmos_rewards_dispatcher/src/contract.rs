@@ -16,13 +16,29 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    attr, to_binary, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, to_binary, Api, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Order, QueryRequest, Reply, Response, StdError, StdResult, SubMsg, Uint128,
+    WasmMsg, WasmQuery,
 };
 
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{Config, CONFIG};
-use basset::hub::{is_paused, ExecuteMsg::BondRewards};
+use crate::msg::{
+    ExecuteMsg, GetBufferedRewardsResponse, InstantiateMsg, LidoFeeRecipient, MigrateMsg, QueryMsg,
+    SimulationResponse, SwapExecuteMsg, SwapQueryMsg, SwapRouteResponse,
+};
+use crate::state::{Config, FeeRecipient, SwapRoute, CONFIG, LEGACY_CONFIG, SWAP_ROUTES};
+use basset::access_control::{ensure_owner_or_role, grant_role, query_roles, revoke_role, Role};
+use basset::hub::{
+    is_paused, ConfigResponse as HubConfig, ExecuteMsg::BondRewards, ExecuteMsg::FundInsurance,
+    PausableOperation, QueryMsg as HubQueryMsg,
+};
+use basset::ownership::{
+    accept_ownership, cancel_ownership_proposal, propose_new_owner, query_pending_owner,
+};
+use lido_cosmos_validators_registry::msg::QueryMsg as QueryValidators;
+use lido_cosmos_validators_registry::registry::Validator;
+
+// Reply id of the swaps, a failed swap leaves the offered rewards on the dispatcher balance
+const SWAP_REPLY_ID: u64 = 1;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -35,9 +51,12 @@ pub fn instantiate(
         owner: info.sender,
         hub_contract: deps.api.addr_validate(&msg.hub_contract)?,
         statom_reward_denom: msg.statom_reward_denom,
-        lido_fee_address: deps.api.addr_validate(&msg.lido_fee_address)?,
+        lido_fee_recipients: validate_lido_fee_recipients(deps.api, msg.lido_fee_recipients)?,
         lido_fee_rate: msg.lido_fee_rate,
+        operator_fee_rate: msg.operator_fee_rate,
+        insurance_fee_rate: Decimal::zero(),
     };
+    validate_fee_rates(&conf)?;
 
     CONFIG.save(deps.storage, &conf)?;
 
@@ -47,22 +66,59 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
-        ExecuteMsg::DispatchRewards {} => execute_dispatch_rewards(deps, env, info),
+        ExecuteMsg::DispatchRewards { validator_rewards } => {
+            execute_dispatch_rewards(deps, env, info, validator_rewards)
+        }
+        ExecuteMsg::DispatchUnderlyingRewards { validator_rewards } => {
+            execute_dispatch_underlying_rewards(deps, env, info, validator_rewards)
+        }
+        ExecuteMsg::SetSwapRoute {
+            denom,
+            swap_contract,
+            price,
+            max_spread,
+        } => execute_set_swap_route(deps, env, info, denom, swap_contract, price, max_spread),
+        ExecuteMsg::RemoveSwapRoute { denom } => execute_remove_swap_route(deps, env, info, denom),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let conf = CONFIG.load(deps.storage)?;
+            propose_new_owner(deps, env, info, &conf.owner, owner, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info, |deps, owner| {
+            CONFIG.update(deps.storage, |mut conf| -> StdResult<_> {
+                conf.owner = owner;
+                Ok(conf)
+            })?;
+            Ok(())
+        }),
+        ExecuteMsg::CancelOwnershipProposal {} => {
+            let conf = CONFIG.load(deps.storage)?;
+            cancel_ownership_proposal(deps, info, &conf.owner)
+        }
+        ExecuteMsg::GrantRole { role, address } => {
+            let conf = CONFIG.load(deps.storage)?;
+            grant_role(deps, info, &conf.owner, role, address)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let conf = CONFIG.load(deps.storage)?;
+            revoke_role(deps, info, &conf.owner, role, address)
+        }
         ExecuteMsg::UpdateConfig {
-            owner,
             hub_contract,
             statom_reward_denom,
-            lido_fee_address,
+            lido_fee_recipients,
             lido_fee_rate,
+            operator_fee_rate,
+            insurance_fee_rate,
         } => execute_update_config(
             deps,
             env,
             info,
-            owner,
             hub_contract,
             statom_reward_denom,
-            lido_fee_address,
+            lido_fee_recipients,
             lido_fee_rate,
+            operator_fee_rate,
+            insurance_fee_rate,
         ),
     }
 }
@@ -72,25 +128,22 @@ pub fn execute_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    owner: Option<String>,
     hub_contract: Option<String>,
     statom_reward_denom: Option<String>,
-    lido_fee_address: Option<String>,
+    lido_fee_recipients: Option<Vec<LidoFeeRecipient>>,
     lido_fee_rate: Option<Decimal>,
+    operator_fee_rate: Option<Decimal>,
+    insurance_fee_rate: Option<Decimal>,
 ) -> StdResult<Response> {
     let conf: Config = CONFIG.load(deps.storage)?;
     let sender_raw = deps.api.addr_validate(info.sender.as_str())?;
-    if sender_raw != conf.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
-
-    if let Some(o) = owner {
-        let owner_raw = deps.api.addr_validate(&o)?;
-
-        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
-            last_config.owner = owner_raw;
-            Ok(last_config)
-        })?;
+    // fee managers can change the fees only, the contracts are changed by the owner
+    if hub_contract.is_some() || statom_reward_denom.is_some() {
+        if sender_raw != conf.owner {
+            return Err(StdError::generic_err("unauthorized"));
+        }
+    } else {
+        ensure_owner_or_role(deps.storage, &conf.owner, &sender_raw, Role::FeeManager)?;
     }
 
     if let Some(h) = hub_contract {
@@ -111,15 +164,36 @@ pub fn execute_update_config(
     if let Some(r) = lido_fee_rate {
         CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
             last_config.lido_fee_rate = r;
+            validate_fee_rates(&last_config)?;
             Ok(last_config)
         })?;
     }
 
-    if let Some(a) = lido_fee_address {
-        let address_raw = deps.api.addr_validate(&a)?;
+    if let Some(r) = operator_fee_rate {
+        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
+            last_config.operator_fee_rate = r;
+            validate_fee_rates(&last_config)?;
+            Ok(last_config)
+        })?;
+    }
 
+    if let Some(r) = insurance_fee_rate {
+        if r > Decimal::one() {
+            return Err(StdError::generic_err(
+                "insurance_fee_rate must not be greater than 1",
+            ));
+        }
         CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
-            last_config.lido_fee_address = address_raw;
+            last_config.insurance_fee_rate = r;
+            Ok(last_config)
+        })?;
+    }
+
+    if let Some(recipients) = lido_fee_recipients {
+        let recipients_raw = validate_lido_fee_recipients(deps.api, recipients)?;
+
+        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
+            last_config.lido_fee_recipients = recipients_raw;
             Ok(last_config)
         })?;
     }
@@ -127,35 +201,325 @@ pub fn execute_update_config(
     Ok(Response::default())
 }
 
-pub fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+fn validate_fee_rates(config: &Config) -> StdResult<()> {
+    if config.lido_fee_rate + config.operator_fee_rate > Decimal::one() {
+        return Err(StdError::generic_err(
+            "sum of lido_fee_rate and operator_fee_rate must not be greater than 1",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_lido_fee_recipients(
+    api: &dyn Api,
+    recipients: Vec<LidoFeeRecipient>,
+) -> StdResult<Vec<FeeRecipient>> {
+    if recipients.is_empty() {
+        return Err(StdError::generic_err(
+            "at least one lido fee recipient is required",
+        ));
+    }
+
+    let mut total_weight = Decimal::zero();
+    let mut recipients_raw: Vec<FeeRecipient> = vec![];
+    for r in recipients {
+        let address = api.addr_validate(&r.address)?;
+        if r.weight.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "lido fee recipient {} has zero weight",
+                address
+            )));
+        }
+        if recipients_raw.iter().any(|v| v.address == address) {
+            return Err(StdError::generic_err(format!(
+                "duplicated lido fee recipient {}",
+                address
+            )));
+        }
+        total_weight = total_weight + r.weight;
+        recipients_raw.push(FeeRecipient {
+            address,
+            weight: r.weight,
+        });
+    }
+
+    if total_weight != Decimal::one() {
+        return Err(StdError::generic_err(
+            "lido fee recipients weights must sum to 1",
+        ));
+    }
+
+    Ok(recipients_raw)
+}
+
+pub fn execute_set_swap_route(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+    swap_contract: String,
+    price: Decimal,
+    max_spread: Decimal,
+) -> StdResult<Response> {
+    let conf: Config = CONFIG.load(deps.storage)?;
+    if info.sender != conf.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if denom == conf.statom_reward_denom {
+        return Err(StdError::generic_err(
+            "statom reward denom cannot have a swap route",
+        ));
+    }
+    if price.is_zero() {
+        return Err(StdError::generic_err("price must be greater than 0"));
+    }
+    if max_spread > Decimal::one() {
+        return Err(StdError::generic_err(
+            "max_spread must not be greater than 1",
+        ));
+    }
+
+    let route = SwapRoute {
+        swap_contract: deps.api.addr_validate(&swap_contract)?,
+        price,
+        max_spread,
+    };
+    SWAP_ROUTES.save(deps.storage, &denom, &route)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_swap_route"),
+        attr("denom", denom),
+        attr("swap_contract", swap_contract),
+    ]))
+}
+
+pub fn execute_remove_swap_route(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> StdResult<Response> {
+    let conf: Config = CONFIG.load(deps.storage)?;
+    if info.sender != conf.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    SWAP_ROUTES.remove(deps.storage, &denom);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_swap_route"),
+        attr("denom", denom),
+    ]))
+}
+
+/// Swaps every reward denom that has a swap route into statom_reward_denom.
+/// If nothing has to be swapped the underlying rewards are dispatched right away,
+/// otherwise the dispatcher calls itself after the swaps to dispatch the proceeds.
+pub fn execute_dispatch_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<Response> {
     let config: Config = CONFIG.load(deps.storage)?;
-    if is_paused(deps.as_ref(), config.hub_contract.clone().into_string())? {
+    if is_paused(
+        deps.as_ref(),
+        config.hub_contract.clone().into_string(),
+        PausableOperation::Rewards,
+    )? {
         return Err(StdError::generic_err("the contract is temporarily paused"));
     }
 
-    let hub_addr = config.hub_contract;
-    if info.sender != hub_addr {
+    if info.sender != config.hub_contract {
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let contr_addr = env.contract.address;
-    let mut statom_rewards = deps
-        .querier
-        .query_balance(contr_addr, config.statom_reward_denom.clone())?;
+    let (swap_msgs, swap_attrs) = compute_swaps(deps.as_ref(), &env, &config)?;
+    if swap_msgs.is_empty() {
+        return Ok(
+            dispatch_underlying_rewards(deps.as_ref(), &env, config, validator_rewards)?
+                .add_attributes(swap_attrs),
+        );
+    }
+
+    Ok(Response::new()
+        .add_submessages(swap_msgs)
+        .add_message(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::DispatchUnderlyingRewards { validator_rewards })?,
+            funds: vec![],
+        })
+        .add_attribute("action", "swap_rewards")
+        .add_attributes(swap_attrs))
+}
+
+pub fn execute_dispatch_underlying_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<Response> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    dispatch_underlying_rewards(deps.as_ref(), &env, config, validator_rewards)
+}
+
+/// Swaps that failed are reverted and their rewards stay buffered until the next dispatch
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    if msg.id != SWAP_REPLY_ID {
+        return Err(StdError::generic_err(format!(
+            "unknown reply id {}",
+            msg.id
+        )));
+    }
+
+    match msg.result.into_result() {
+        Ok(_) => Ok(Response::default()),
+        Err(error) => Ok(Response::new()
+            .add_attributes(vec![attr("action", "swap_failed"), attr("error", error)])),
+    }
+}
 
-    let lido_statom_fee_amount = compute_lido_fee(statom_rewards.amount, config.lido_fee_rate);
-    statom_rewards.amount = statom_rewards.amount.checked_sub(lido_statom_fee_amount)?;
+/// Builds swap messages for all non-underlying balances that have a swap route.
+/// Denoms without a route, or whose simulation fails or returns less than the route's
+/// minimum, are left on the dispatcher balance.
+fn compute_swaps(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> StdResult<(Vec<SubMsg>, Vec<Attribute>)> {
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut attrs: Vec<Attribute> = vec![];
+
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+    for offer in balances {
+        if offer.denom == config.statom_reward_denom || offer.amount.is_zero() {
+            continue;
+        }
+        let route = match SWAP_ROUTES.may_load(deps.storage, &offer.denom)? {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let price_amount = offer.amount * route.price;
+        let min_receive = price_amount.checked_sub(price_amount * route.max_spread)?;
+        if min_receive.is_zero() {
+            continue;
+        }
+
+        let simulation: StdResult<SimulationResponse> =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: route.swap_contract.to_string(),
+                msg: to_binary(&SwapQueryMsg::Simulation {
+                    offer: offer.clone(),
+                    ask_denom: config.statom_reward_denom.clone(),
+                })?,
+            }));
+        match simulation {
+            Ok(s) if s.return_amount >= min_receive => {}
+            _ => {
+                attrs.push(attr("swap_skipped", offer.to_string()));
+                continue;
+            }
+        }
+
+        attrs.push(attr("swapped", offer.to_string()));
+        messages.push(SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: route.swap_contract.to_string(),
+                msg: to_binary(&SwapExecuteMsg::Swap {
+                    ask_denom: config.statom_reward_denom.clone(),
+                    min_receive,
+                })?,
+                funds: vec![offer],
+            },
+            SWAP_REPLY_ID,
+        ));
+    }
+
+    Ok((messages, attrs))
+}
+
+/// Sends the balance in statom_reward_denom to the hub as BondRewards, splits the lido fee
+/// among lido_fee_recipients and pays the operator fee to the validators reward addresses
+fn dispatch_underlying_rewards(
+    deps: Deps,
+    env: &Env,
+    config: Config,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<Response> {
+    let hub_addr = config.hub_contract.clone();
+    let split = compute_rewards_split(deps, env, &config, validator_rewards)?;
+    let statom_rewards = Coin::new(
+        split.statom_rewards.u128(),
+        config.statom_reward_denom.as_str(),
+    );
 
     let mut fees_attrs: Vec<Attribute> = vec![];
+    let mut fees_msgs: Vec<CosmosMsg> = vec![];
+
+    for (validator, reward_address, amount) in split.operator_fees {
+        let fee = Coin::new(amount.u128(), config.statom_reward_denom.as_str());
+        fees_attrs.push(attr(
+            "operator_statom_fee",
+            format!("{}:{}", validator, fee),
+        ));
+        fees_msgs.push(
+            BankMsg::Send {
+                to_address: reward_address,
+                amount: vec![fee],
+            }
+            .into(),
+        );
+    }
 
-    let mut lido_fees: Vec<Coin> = vec![];
-    if !lido_statom_fee_amount.is_zero() {
+    if !split.lido_fee.is_zero() {
         let statom_fee = Coin {
-            amount: lido_statom_fee_amount,
+            amount: split.lido_fee,
             denom: config.statom_reward_denom.clone(),
         };
-        lido_fees.push(statom_fee.clone());
         fees_attrs.push(attr("lido_statom_fee", statom_fee.to_string()));
+
+        // a share of the fee funds the hub's insurance reserve, the rest goes to the recipients
+        if !split.insurance_fee.is_zero() {
+            let insurance_fee = Coin::new(
+                split.insurance_fee.u128(),
+                config.statom_reward_denom.as_str(),
+            );
+            fees_attrs.push(attr("insurance_statom_fee", insurance_fee.to_string()));
+            fees_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hub_addr.to_string(),
+                msg: to_binary(&FundInsurance {})?,
+                funds: vec![insurance_fee],
+            }));
+        }
+
+        let recipients_split = split_lido_fee(
+            split.lido_fee.checked_sub(split.insurance_fee)?,
+            &config.lido_fee_recipients,
+        );
+        for (recipient, amount) in config.lido_fee_recipients.iter().zip(recipients_split) {
+            if amount.is_zero() {
+                continue;
+            }
+            let fee = Coin::new(amount.u128(), config.statom_reward_denom.as_str());
+            fees_attrs.push(attr(
+                "lido_statom_fee_recipient",
+                format!("{}:{}", recipient.address, fee),
+            ));
+            fees_msgs.push(
+                BankMsg::Send {
+                    to_address: recipient.address.to_string(),
+                    amount: vec![fee],
+                }
+                .into(),
+            );
+        }
     }
     let mut messages: Vec<CosmosMsg> = vec![];
     if !statom_rewards.amount.is_zero() {
@@ -165,15 +529,7 @@ pub fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> S
             funds: vec![statom_rewards.clone()],
         }));
     }
-    if !lido_fees.is_empty() {
-        messages.push(
-            BankMsg::Send {
-                to_address: config.lido_fee_address.to_string(),
-                amount: lido_fees,
-            }
-            .into(),
-        )
-    }
+    messages.append(&mut fees_msgs);
 
     Ok(Response::new()
         .add_messages(messages)
@@ -184,24 +540,171 @@ pub fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> S
         .add_attributes(fees_attrs))
 }
 
+/// How the dispatcher balance in statom_reward_denom is split by a dispatch
+struct RewardsSplit {
+    total: Uint128,
+    /// The whole lido fee, including the insurance share
+    lido_fee: Uint128,
+    insurance_fee: Uint128,
+    /// (validator, reward address, fee)
+    operator_fees: Vec<(String, String, Uint128)>,
+    /// The rest of the balance, bonded by the hub
+    statom_rewards: Uint128,
+}
+
+fn compute_rewards_split(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<RewardsSplit> {
+    let total = deps
+        .querier
+        .query_balance(&env.contract.address, config.statom_reward_denom.clone())?
+        .amount;
+    let lido_fee = compute_lido_fee(total, config.lido_fee_rate);
+    let insurance_fee = lido_fee * config.insurance_fee_rate;
+    let operator_fees = compute_operator_fees(deps, config, validator_rewards)?;
+
+    let mut statom_rewards = total.checked_sub(lido_fee)?;
+    for (_, _, amount) in operator_fees.iter() {
+        statom_rewards = statom_rewards.checked_sub(*amount)?;
+    }
+
+    Ok(RewardsSplit {
+        total,
+        lido_fee,
+        insurance_fee,
+        operator_fees,
+        statom_rewards,
+    })
+}
+
+/// Returns (validator, reward address, fee) for every validator that has a reward address
+/// in the registry and produced non-zero fee
+fn compute_operator_fees(
+    deps: Deps,
+    config: &Config,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<Vec<(String, String, Uint128)>> {
+    if config.operator_fee_rate.is_zero() || validator_rewards.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let hub_config: HubConfig = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: config.hub_contract.to_string(),
+        msg: to_binary(&HubQueryMsg::Config {})?,
+    }))?;
+    let validators_registry_contract = match hub_config.validators_registry_contract {
+        Some(v) => v,
+        None => return Ok(vec![]),
+    };
+    let validators: Vec<Validator> = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: validators_registry_contract,
+        msg: to_binary(&QueryValidators::GetValidators {})?,
+    }))?;
+
+    let mut fees: Vec<(String, String, Uint128)> = vec![];
+    for (validator_address, reward) in validator_rewards {
+        let reward_address = validators
+            .iter()
+            .find(|v| v.address == validator_address)
+            .and_then(|v| v.reward_address.clone());
+        let fee = compute_lido_fee(reward, config.operator_fee_rate);
+        if let Some(reward_address) = reward_address {
+            if !fee.is_zero() {
+                fees.push((validator_address, reward_address, fee));
+            }
+        }
+    }
+
+    Ok(fees)
+}
+
 fn query_config(deps: Deps) -> StdResult<Config> {
     let config = CONFIG.load(deps.storage)?;
     Ok(config)
 }
 
+fn query_swap_routes(deps: Deps) -> StdResult<Vec<SwapRouteResponse>> {
+    SWAP_ROUTES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, route) = item?;
+            Ok(SwapRouteResponse {
+                denom: String::from_utf8(denom)?,
+                swap_contract: route.swap_contract.to_string(),
+                price: route.price,
+                max_spread: route.max_spread,
+            })
+        })
+        .collect()
+}
+
+fn query_buffered_rewards(
+    deps: Deps,
+    env: Env,
+    validator_rewards: Vec<(String, Uint128)>,
+) -> StdResult<GetBufferedRewardsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let split = compute_rewards_split(deps, &env, &config, validator_rewards)?;
+
+    Ok(GetBufferedRewardsResponse {
+        total_statom_rewards: split.total,
+        lido_statom_fee: split.lido_fee,
+        insurance_statom_fee: split.insurance_fee,
+        operator_statom_fee: split.operator_fees.iter().map(|(_, _, fee)| fee).sum(),
+        statom_rewards: split.statom_rewards,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::GetBufferedRewards {} => unimplemented!(),
+        QueryMsg::GetBufferedRewards { validator_rewards } => {
+            to_binary(&query_buffered_rewards(deps, env, validator_rewards)?)
+        }
+        QueryMsg::SwapRoutes {} => to_binary(&query_swap_routes(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::Roles {} => to_binary(&query_roles(deps)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // the whole fee of a legacy config goes to its single lido_fee_address
+    if CONFIG.load(deps.storage).is_err() {
+        let legacy = LEGACY_CONFIG.load(deps.storage)?;
+        let conf = Config {
+            owner: legacy.owner,
+            hub_contract: legacy.hub_contract,
+            statom_reward_denom: legacy.statom_reward_denom,
+            lido_fee_recipients: vec![FeeRecipient {
+                address: legacy.lido_fee_address,
+                weight: Decimal::one(),
+            }],
+            lido_fee_rate: legacy.lido_fee_rate,
+            operator_fee_rate: Decimal::zero(),
+            insurance_fee_rate: Decimal::zero(),
+        };
+        CONFIG.save(deps.storage, &conf)?;
+    }
+
     Ok(Response::default())
 }
 
 pub fn compute_lido_fee(amount: Uint128, fee_rate: Decimal) -> Uint128 {
     amount * fee_rate
 }
+
+/// Splits the fee according to the recipients weights.
+/// The rounding remainder goes to the first recipient.
+pub fn split_lido_fee(fee: Uint128, recipients: &[FeeRecipient]) -> Vec<Uint128> {
+    let mut split: Vec<Uint128> = recipients.iter().map(|r| fee * r.weight).collect();
+    let distributed: Uint128 = split.iter().sum();
+    if let Some(first) = split.first_mut() {
+        *first += fee - distributed;
+    }
+    split
+}
@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use cosmwasm_std::Decimal;
+use basset::access_control::Role;
+use cosmwasm_std::{Coin, Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -20,35 +21,128 @@ use serde::{Deserialize, Serialize};
 pub struct InstantiateMsg {
     pub hub_contract: String,
     pub statom_reward_denom: String,
-    pub lido_fee_address: String,
+    pub lido_fee_recipients: Vec<LidoFeeRecipient>,
     pub lido_fee_rate: Decimal,
+    pub operator_fee_rate: Decimal,
+}
+
+/// A recipient of the lido fee. Weights of all recipients must sum to 1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LidoFeeRecipient {
+    pub address: String,
+    pub weight: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     UpdateConfig {
-        owner: Option<String>,
         hub_contract: Option<String>,
         statom_reward_denom: Option<String>,
-        lido_fee_address: Option<String>,
+        lido_fee_recipients: Option<Vec<LidoFeeRecipient>>,
         lido_fee_rate: Option<Decimal>,
+        operator_fee_rate: Option<Decimal>,
+        insurance_fee_rate: Option<Decimal>,
+    },
+    /// Proposes a new owner, who has to accept the ownership within `expires_in` seconds
+    ProposeNewOwner { owner: String, expires_in: u64 },
+    /// Accepts the proposed ownership. Only the proposed owner can execute
+    AcceptOwnership {},
+    /// Cancels the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grants the role to the address
+    GrantRole { role: Role, address: String },
+    /// Revokes the role from the address
+    RevokeRole { role: Role, address: String },
+    /// Swaps non-underlying rewards and sends the underlying ones to the hub.
+    /// `validator_rewards` are the rewards in statom_reward_denom withdrawn from each validator,
+    /// used to pay the operator fee.
+    /// Only the hub is allowed to call it
+    DispatchRewards {
+        #[serde(default)]
+        validator_rewards: Vec<(String, Uint128)>,
+    },
+    /// Bonds rewards in statom_reward_denom once the swaps are done.
+    /// Internal, sent by the dispatcher to itself
+    DispatchUnderlyingRewards {
+        #[serde(default)]
+        validator_rewards: Vec<(String, Uint128)>,
+    },
+    /// Routes rewards in `denom` through `swap_contract`. Only the owner can set routes.
+    /// A swap must return at least `price * (1 - max_spread)` per offered unit
+    SetSwapRoute {
+        denom: String,
+        swap_contract: String,
+        price: Decimal,
+        max_spread: Decimal,
     },
-    DispatchRewards {},
+    /// Stops swapping rewards in `denom`
+    RemoveSwapRoute { denom: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // GetBufferedRewards returns the buffered amount of stAtom rewards.
-    GetBufferedRewards {},
+    // GetBufferedRewards returns the buffered amount of stAtom rewards and how DispatchRewards
+    // would split it given the same validator_rewards.
+    GetBufferedRewards {
+        #[serde(default)]
+        validator_rewards: Vec<(String, Uint128)>,
+    },
     // Config returns config
     Config {},
+    // SwapRoutes returns all configured swap routes
+    SwapRoutes {},
+    // PendingOwner returns the proposed owner who hasn't accepted the ownership yet
+    PendingOwner {},
+    // Roles returns the addresses granted each role
+    Roles {},
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct GetBufferedRewardsResponse {}
+pub struct GetBufferedRewardsResponse {
+    /// Current balance of the dispatcher in statom_reward_denom
+    pub total_statom_rewards: Uint128,
+    /// The part of the balance that is split among lido_fee_recipients and the insurance reserve
+    pub lido_statom_fee: Uint128,
+    /// The share of lido_statom_fee that funds the hub's insurance reserve
+    pub insurance_statom_fee: Uint128,
+    /// The sum of the operator fees paid to the validators reward addresses
+    pub operator_statom_fee: Uint128,
+    /// The part of the balance that is sent to the hub as BondRewards
+    pub statom_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapRouteResponse {
+    pub denom: String,
+    pub swap_contract: String,
+    pub price: Decimal,
+    pub max_spread: Decimal,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
+
+/// The interface a swap contract must implement to be used in a swap route.
+/// The offered coin is sent along with the message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapExecuteMsg {
+    Swap {
+        ask_denom: String,
+        min_receive: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapQueryMsg {
+    Simulation { offer: Coin, ask_denom: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulationResponse {
+    pub return_amount: Uint128,
+}
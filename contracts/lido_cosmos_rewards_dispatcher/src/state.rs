@@ -17,15 +17,52 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{Addr, Decimal};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 pub static CONFIG: Item<Config> = Item::new("config");
 
+// Config stored by the dispatchers deployed before lido_fee_recipients, converted on migration
+pub static LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
+// Swap routes for non-underlying reward denoms, keyed by the offered denom
+pub static SWAP_ROUTES: Map<&str, SwapRoute> = Map::new("swap_routes");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    pub owner: Addr,
+    pub hub_contract: Addr,
+    pub statom_reward_denom: String,
+    pub lido_fee_recipients: Vec<FeeRecipient>,
+    pub lido_fee_rate: Decimal,
+    /// Share of each validator's rewards paid to the validator's reward address from the registry
+    #[serde(default)]
+    pub operator_fee_rate: Decimal,
+    /// Share of the lido fee sent to the hub's insurance reserve covering slashing
+    #[serde(default)]
+    pub insurance_fee_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
     pub owner: Addr,
     pub hub_contract: Addr,
     pub statom_reward_denom: String,
     pub lido_fee_address: Addr,
     pub lido_fee_rate: Decimal,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeRecipient {
+    pub address: Addr,
+    pub weight: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapRoute {
+    /// Contract implementing SwapExecuteMsg/SwapQueryMsg
+    pub swap_contract: Addr,
+    /// Price of one offered unit in statom_reward_denom set by the owner
+    pub price: Decimal,
+    /// Max allowed difference between the amount at `price` and the received amount
+    pub max_spread: Decimal,
+}
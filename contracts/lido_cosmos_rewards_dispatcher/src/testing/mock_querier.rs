@@ -12,17 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use basset::hub::{Parameters, QueryMsg};
+use crate::msg::{SimulationResponse, SwapQueryMsg};
+use basset::hub::{ConfigResponse, Parameters, QueryMsg};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Coin, ContractResult, CustomQuery, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, WasmQuery,
+    from_binary, from_slice, to_binary, Coin, ContractResult, CustomQuery, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
 };
+use lido_cosmos_validators_registry::msg::QueryMsg as QueryValidators;
+use lido_cosmos_validators_registry::registry::Validator;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const MOCK_HUB_CONTRACT_ADDR: &str = "hub";
 pub const MOCK_LIDO_FEE_ADDRESS: &str = "lido_fee";
+// returns half of the offered amount for any denom
+pub const MOCK_SWAP_CONTRACT_ADDR: &str = "swap";
+// fails every simulation, e.g. because its pool doesn't exist
+pub const MOCK_FAILING_SWAP_CONTRACT_ADDR: &str = "failing_swap";
+pub const MOCK_VALIDATORS_REGISTRY_ADDR: &str = "validators_registry";
+// validator with a reward address in the registry
+pub const MOCK_VALIDATOR_WITH_REWARD_ADDR: &str = "validator1";
+pub const MOCK_OPERATOR_REWARD_ADDR: &str = "operator1";
+// validator without a reward address in the registry
+pub const MOCK_VALIDATOR_ADDR: &str = "validator2";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -76,11 +89,70 @@ impl WasmMockQuerier {
                             underlying_coin_denom: "".to_string(),
                             unbonding_period: 0,
                             paused: None,
+                            liquidity_buffer_target: Default::default(),
+                            instant_unbond_fee: Default::default(),
+                            max_insurance_cover: Default::default(),
+                            min_bond_amount: Default::default(),
+                            min_total_supply: Default::default(),
+                            max_exchange_rate_increase: None,
+                            timelock_period: 0,
+                            paused_operations: 0,
+                            max_guardian_pause_duration: 0,
+                            guardian_pause_cooldown: 0,
+                            pause_info: None,
+                            operation_pauses: vec![],
                         };
                         SystemResult::Ok(ContractResult::from(to_binary(&params)))
+                    } else if msg == &to_binary(&QueryMsg::Config {}).unwrap() {
+                        let config = ConfigResponse {
+                            owner: "owner".to_string(),
+                            reward_dispatcher_contract: Some(MOCK_CONTRACT_ADDR.to_string()),
+                            validators_registry_contract: Some(
+                                MOCK_VALIDATORS_REGISTRY_ADDR.to_string(),
+                            ),
+                            statom_token_contract: None,
+                            claims_nft_contract: None,
+                        };
+                        SystemResult::Ok(ContractResult::from(to_binary(&config)))
                     } else {
                         unimplemented!()
                     }
+                } else if *contract_addr == MOCK_VALIDATORS_REGISTRY_ADDR {
+                    match from_binary(msg).unwrap() {
+                        QueryValidators::GetValidators {} => {
+                            let validators = vec![
+                                Validator {
+                                    address: MOCK_VALIDATOR_WITH_REWARD_ADDR.to_string(),
+                                    reward_address: Some(MOCK_OPERATOR_REWARD_ADDR.to_string()),
+                                    moniker: None,
+                                    weight: None,
+                                    max_delegation: None,
+                                    active: true,
+                                },
+                                Validator {
+                                    address: MOCK_VALIDATOR_ADDR.to_string(),
+                                    reward_address: None,
+                                    moniker: None,
+                                    weight: None,
+                                    max_delegation: None,
+                                    active: true,
+                                },
+                            ];
+                            SystemResult::Ok(ContractResult::from(to_binary(&validators)))
+                        }
+                        _ => unimplemented!(),
+                    }
+                } else if *contract_addr == MOCK_SWAP_CONTRACT_ADDR {
+                    match from_binary(msg).unwrap() {
+                        SwapQueryMsg::Simulation { offer, .. } => {
+                            let res = SimulationResponse {
+                                return_amount: offer.amount.multiply_ratio(1u128, 2u128),
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                    }
+                } else if *contract_addr == MOCK_FAILING_SWAP_CONTRACT_ADDR {
+                    SystemResult::Ok(ContractResult::Err("pool not found".to_string()))
                 } else {
                     unimplemented!()
                 }
@@ -12,44 +12,64 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! This integration test tries to run and call the generated wasm.
-//! It depends on a Wasm build being available, which you can create with `cargo wasm`.
-//! Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
-//!
-//! You can easily convert unit tests to integration tests as follows:
-//! 1. Copy them over verbatim
-//! 2. Then change
-//!      let mut deps = mock_dependencies(&[]);
-//!    to
-//!      let mut deps = mock_instance(WASM, &[]);
-//! 3. If you access raw storage, where ever you see something like:
-//!      deps.storage.get(CONFIG_KEY).expect("no data stored");
-//!    replace it with:
-//!      deps.with_storage(|store| {
-//!          let data = store.get(CONFIG_KEY).expect("no data stored");
-//!          //...
-//!      });
-//! 4. Anywhere you see query(&deps, ...) you must replace it with query(deps.as_mut(), ...)
+// This integration test tries to run and call the generated wasm.
+// It depends on a Wasm build being available, which you can create with `cargo wasm`.
+// Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
+//
+// You can easily convert unit tests to integration tests as follows:
+// 1. Copy them over verbatim
+// 2. Then change
+//      let mut deps = mock_dependencies(&[]);
+//    to
+//      let mut deps = mock_instance(WASM, &[]);
+// 3. If you access raw storage, where ever you see something like:
+//      deps.storage.get(CONFIG_KEY).expect("no data stored");
+//    replace it with:
+//      deps.with_storage(|store| {
+//          let data = store.get(CONFIG_KEY).expect("no data stored");
+//          //...
+//      });
+// 4. Anywhere you see query(&deps, ...) you must replace it with query(deps.as_mut(), ...)
 
+use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
 use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{coins, Api, Coin, Decimal, StdError, Uint128};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, Addr, Api, BankMsg, Coin, ContractResult, CosmosMsg, Decimal,
+    Reply, ReplyOn, StdError, Uint128, WasmMsg,
+};
 
-use crate::contract::{execute, instantiate};
-use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::state::CONFIG;
+use crate::contract::{execute, instantiate, migrate, query, reply};
+use crate::msg::{
+    ExecuteMsg, GetBufferedRewardsResponse, InstantiateMsg, LidoFeeRecipient, MigrateMsg, QueryMsg,
+    SwapExecuteMsg, SwapRouteResponse,
+};
+use crate::state::{Config, FeeRecipient, LegacyConfig, CONFIG, LEGACY_CONFIG};
 use crate::testing::mock_querier::{
-    mock_dependencies, MOCK_HUB_CONTRACT_ADDR, MOCK_LIDO_FEE_ADDRESS,
+    mock_dependencies, MOCK_FAILING_SWAP_CONTRACT_ADDR, MOCK_HUB_CONTRACT_ADDR,
+    MOCK_LIDO_FEE_ADDRESS, MOCK_OPERATOR_REWARD_ADDR, MOCK_SWAP_CONTRACT_ADDR, MOCK_VALIDATOR_ADDR,
+    MOCK_VALIDATOR_WITH_REWARD_ADDR,
 };
+use basset::access_control::{Role, RolesResponse};
+use basset::hub::ExecuteMsg::{BondRewards, FundInsurance};
+use basset::ownership::PendingOwnerResponse;
 
 fn default_init() -> InstantiateMsg {
     InstantiateMsg {
         hub_contract: String::from(MOCK_HUB_CONTRACT_ADDR),
         statom_reward_denom: "uatom".to_string(),
-        lido_fee_address: String::from(MOCK_LIDO_FEE_ADDRESS),
+        lido_fee_recipients: vec![LidoFeeRecipient {
+            address: String::from(MOCK_LIDO_FEE_ADDRESS),
+            weight: Decimal::one(),
+        }],
         lido_fee_rate: Decimal::from_ratio(Uint128::from(5u64), Uint128::from(100u64)),
+        operator_fee_rate: Decimal::zero(),
     }
 }
 
+fn buffered_rewards_query(validator_rewards: Vec<(String, Uint128)>) -> QueryMsg {
+    QueryMsg::GetBufferedRewards { validator_rewards }
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -78,7 +98,9 @@ fn test_dispatch_rewards() {
     assert_eq!(0, res.messages.len());
 
     let info = mock_info(String::from(MOCK_HUB_CONTRACT_ADDR).as_str(), &[]);
-    let msg = ExecuteMsg::DispatchRewards {};
+    let msg = ExecuteMsg::DispatchRewards {
+        validator_rewards: vec![],
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(2, res.messages.len());
@@ -99,8 +121,12 @@ fn test_dispatch_rewards_zero_lido_fee() {
     let msg = InstantiateMsg {
         hub_contract: String::from(MOCK_HUB_CONTRACT_ADDR),
         statom_reward_denom: "uatom".to_string(),
-        lido_fee_address: String::from(MOCK_LIDO_FEE_ADDRESS),
+        lido_fee_recipients: vec![LidoFeeRecipient {
+            address: String::from(MOCK_LIDO_FEE_ADDRESS),
+            weight: Decimal::one(),
+        }],
         lido_fee_rate: Decimal::zero(),
+        operator_fee_rate: Decimal::zero(),
     };
     let info = mock_info("creator", &[]);
 
@@ -109,7 +135,9 @@ fn test_dispatch_rewards_zero_lido_fee() {
     assert_eq!(0, res.messages.len());
 
     let info = mock_info(String::from(MOCK_HUB_CONTRACT_ADDR).as_str(), &[]);
-    let msg = ExecuteMsg::DispatchRewards {};
+    let msg = ExecuteMsg::DispatchRewards {
+        validator_rewards: vec![],
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(1, res.messages.len());
@@ -121,6 +149,644 @@ fn test_dispatch_rewards_zero_lido_fee() {
     }
 }
 
+#[test]
+fn test_dispatch_rewards_multiple_fee_recipients() {
+    let mut deps = mock_dependencies(&[Coin::new(1000, "uatom")]);
+
+    let msg = InstantiateMsg {
+        hub_contract: String::from(MOCK_HUB_CONTRACT_ADDR),
+        statom_reward_denom: "uatom".to_string(),
+        lido_fee_recipients: vec![
+            LidoFeeRecipient {
+                address: String::from("treasury"),
+                weight: Decimal::percent(50),
+            },
+            LidoFeeRecipient {
+                address: String::from("operators"),
+                weight: Decimal::percent(30),
+            },
+            LidoFeeRecipient {
+                address: String::from("insurance"),
+                weight: Decimal::percent(20),
+            },
+        ],
+        lido_fee_rate: Decimal::from_ratio(Uint128::from(33u64), Uint128::from(1000u64)),
+        operator_fee_rate: Decimal::zero(),
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {
+            validator_rewards: vec![],
+        },
+    )
+    .unwrap();
+
+    // 33uatom of fee: 16 + 2 (rounding remainder) / 9 / 6
+    assert_eq!(4, res.messages.len());
+    let expected_fees = [("treasury", 18u128), ("operators", 9), ("insurance", 6)];
+    for (i, (recipient, amount)) in expected_fees.iter().enumerate() {
+        assert_eq!(
+            res.messages[i + 1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin::new(*amount, "uatom")],
+            })
+        );
+    }
+
+    let recipient_attrs: Vec<String> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "lido_statom_fee_recipient")
+        .map(|a| a.value.clone())
+        .collect();
+    assert_eq!(
+        recipient_attrs,
+        vec!["treasury:18uatom", "operators:9uatom", "insurance:6uatom"]
+    );
+    for attr in res.attributes {
+        if attr.key == "statom_rewards" {
+            assert_eq!("967uatom", attr.value)
+        }
+        if attr.key == "lido_statom_fee" {
+            assert_eq!("33uatom", attr.value)
+        }
+    }
+}
+
+#[test]
+fn test_dispatch_rewards_operator_fee() {
+    let mut deps = mock_dependencies(&[Coin::new(1000, "uatom")]);
+
+    let mut msg = default_init();
+    msg.operator_fee_rate = Decimal::percent(10);
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {
+            validator_rewards: vec![
+                (
+                    MOCK_VALIDATOR_WITH_REWARD_ADDR.to_string(),
+                    Uint128::from(600u64),
+                ),
+                (MOCK_VALIDATOR_ADDR.to_string(), Uint128::from(400u64)),
+            ],
+        },
+    )
+    .unwrap();
+
+    // 50uatom of lido fee, 60uatom of operator fee for the validator with a reward address only
+    assert_eq!(3, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_HUB_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&BondRewards {}).unwrap(),
+            funds: vec![Coin::new(890, "uatom")],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_OPERATOR_REWARD_ADDR.to_string(),
+            amount: vec![Coin::new(60, "uatom")],
+        })
+    );
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_LIDO_FEE_ADDRESS.to_string(),
+            amount: vec![Coin::new(50, "uatom")],
+        })
+    );
+    let operator_attrs: Vec<String> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "operator_statom_fee")
+        .map(|a| a.value.clone())
+        .collect();
+    assert_eq!(operator_attrs, vec!["validator1:60uatom"]);
+}
+
+#[test]
+fn test_dispatch_rewards_insurance_fee() {
+    let mut deps = mock_dependencies(&[Coin::new(2000, "uatom")]);
+
+    let owner = String::from("creator");
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        default_init(),
+    )
+    .unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: Some(Decimal::percent(101)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_config_msg,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("insurance_fee_rate must not be greater than 1")
+    );
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: Some(Decimal::percent(20)),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        update_config_msg,
+    )
+    .unwrap();
+
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::DispatchRewards {
+        validator_rewards: vec![],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 5% of the rewards is the lido fee, 20% of which funds the insurance reserve
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|m| m.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_HUB_CONTRACT_ADDR),
+                msg: to_binary(&BondRewards {}).unwrap(),
+                funds: vec![Coin::new(1900, "uatom")],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_HUB_CONTRACT_ADDR),
+                msg: to_binary(&FundInsurance {}).unwrap(),
+                funds: vec![Coin::new(20, "uatom")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from(MOCK_LIDO_FEE_ADDRESS),
+                amount: vec![Coin::new(80, "uatom")],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_fee_rates_sum_exceeds_one() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut msg = default_init();
+    msg.operator_fee_rate = Decimal::percent(96);
+    let info = mock_info("creator", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "sum of lido_fee_rate and operator_fee_rate must not be greater than 1"
+        )
+    );
+
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: None,
+        operator_fee_rate: Some(Decimal::percent(96)),
+        insurance_fee_rate: None,
+    };
+    let info = mock_info("creator", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, update_config_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "sum of lido_fee_rate and operator_fee_rate must not be greater than 1"
+        )
+    );
+}
+
+#[test]
+fn test_get_buffered_rewards() {
+    let mut deps = mock_dependencies(&[Coin::new(200, "uatom"), Coin::new(3200, "uusd")]);
+
+    let msg = default_init();
+    let info = mock_info("creator", &[]);
+
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let res: GetBufferedRewardsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), buffered_rewards_query(vec![])).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        GetBufferedRewardsResponse {
+            total_statom_rewards: Uint128::from(200u64),
+            lido_statom_fee: Uint128::from(10u64),
+            insurance_statom_fee: Uint128::zero(),
+            operator_statom_fee: Uint128::zero(),
+            statom_rewards: Uint128::from(190u64),
+        }
+    );
+
+    // the query must agree with what DispatchRewards actually sends
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {
+            validator_rewards: vec![],
+        },
+    )
+    .unwrap();
+    for attr in res.attributes {
+        if attr.key == "statom_rewards" {
+            assert_eq!("190uatom", attr.value)
+        }
+        if attr.key == "lido_statom_fee" {
+            assert_eq!("10uatom", attr.value)
+        }
+    }
+
+    // empty balance
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let res: GetBufferedRewardsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), buffered_rewards_query(vec![])).unwrap())
+            .unwrap();
+    assert_eq!(res.total_statom_rewards, Uint128::zero());
+    assert_eq!(res.lido_statom_fee, Uint128::zero());
+    assert_eq!(res.statom_rewards, Uint128::zero());
+}
+
+#[test]
+fn test_get_buffered_rewards_with_fees() {
+    let mut deps = mock_dependencies(&[Coin::new(1000, "uatom")]);
+
+    let mut msg = default_init();
+    msg.operator_fee_rate = Decimal::percent(10);
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: Some(Decimal::percent(20)),
+    };
+    execute(deps.as_mut(), mock_env(), info, update_config_msg).unwrap();
+
+    let validator_rewards = vec![
+        (
+            MOCK_VALIDATOR_WITH_REWARD_ADDR.to_string(),
+            Uint128::from(600u64),
+        ),
+        (MOCK_VALIDATOR_ADDR.to_string(), Uint128::from(400u64)),
+    ];
+    let buffered: GetBufferedRewardsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            buffered_rewards_query(validator_rewards.clone()),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        buffered,
+        GetBufferedRewardsResponse {
+            total_statom_rewards: Uint128::from(1000u64),
+            lido_statom_fee: Uint128::from(50u64),
+            insurance_statom_fee: Uint128::from(10u64),
+            operator_statom_fee: Uint128::from(60u64),
+            statom_rewards: Uint128::from(890u64),
+        }
+    );
+
+    // the query agrees with what DispatchRewards sends
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards { validator_rewards },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|m| m.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_HUB_CONTRACT_ADDR),
+                msg: to_binary(&BondRewards {}).unwrap(),
+                funds: vec![Coin::new(buffered.statom_rewards.u128(), "uatom")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_OPERATOR_REWARD_ADDR.to_string(),
+                amount: vec![Coin::new(buffered.operator_statom_fee.u128(), "uatom")],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_HUB_CONTRACT_ADDR),
+                msg: to_binary(&FundInsurance {}).unwrap(),
+                funds: vec![Coin::new(buffered.insurance_statom_fee.u128(), "uatom")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from(MOCK_LIDO_FEE_ADDRESS),
+                amount: vec![Coin::new(
+                    (buffered.lido_statom_fee - buffered.insurance_statom_fee).u128(),
+                    "uatom"
+                )],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_swap_routes() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let set_route = ExecuteMsg::SetSwapRoute {
+        denom: "uusd".to_string(),
+        swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+        price: Decimal::percent(50),
+        max_spread: Decimal::percent(1),
+    };
+
+    // only the owner can set swap routes
+    let info = mock_info("villain", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, set_route.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info("creator", &[]);
+    execute(deps.as_mut(), mock_env(), info, set_route).unwrap();
+
+    // the underlying denom cannot be swapped
+    let info = mock_info("creator", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SetSwapRoute {
+            denom: "uatom".to_string(),
+            swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+            price: Decimal::percent(50),
+            max_spread: Decimal::percent(1),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("statom reward denom cannot have a swap route")
+    );
+
+    // a route without a price gives no slippage protection
+    let info = mock_info("creator", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SetSwapRoute {
+            denom: "uusd".to_string(),
+            swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+            price: Decimal::zero(),
+            max_spread: Decimal::percent(1),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("price must be greater than 0")
+    );
+
+    let routes: Vec<SwapRouteResponse> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SwapRoutes {}).unwrap()).unwrap();
+    assert_eq!(
+        routes,
+        vec![SwapRouteResponse {
+            denom: "uusd".to_string(),
+            swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+            price: Decimal::percent(50),
+            max_spread: Decimal::percent(1),
+        }]
+    );
+
+    let info = mock_info("creator", &[]);
+    let msg = ExecuteMsg::RemoveSwapRoute {
+        denom: "uusd".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let routes: Vec<SwapRouteResponse> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SwapRoutes {}).unwrap()).unwrap();
+    assert!(routes.is_empty());
+}
+
+#[test]
+fn test_dispatch_rewards_with_swaps() {
+    let mut deps = mock_dependencies(&[
+        Coin::new(200, "uatom"),
+        Coin::new(3200, "uusd"),
+        Coin::new(6400, "usdr"),
+    ]);
+
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), default_init()).unwrap();
+
+    let msg = ExecuteMsg::SetSwapRoute {
+        denom: "uusd".to_string(),
+        swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+        price: Decimal::percent(50),
+        max_spread: Decimal::percent(1),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {
+            validator_rewards: vec![],
+        },
+    )
+    .unwrap();
+
+    // uusd is swapped, usdr has no route and stays on the balance
+    assert_eq!(2, res.messages.len());
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&SwapExecuteMsg::Swap {
+                ask_denom: "uatom".to_string(),
+                min_receive: Uint128::from(1584u64),
+            })
+            .unwrap(),
+            funds: vec![Coin::new(3200, "uusd")],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&ExecuteMsg::DispatchUnderlyingRewards {
+                validator_rewards: vec![],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // only the dispatcher itself can bond the proceeds
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchUnderlyingRewards {
+            validator_rewards: vec![],
+        },
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchUnderlyingRewards {
+            validator_rewards: vec![],
+        },
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_HUB_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&BondRewards {}).unwrap(),
+            funds: vec![Coin::new(190, "uatom")],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_LIDO_FEE_ADDRESS.to_string(),
+            amount: vec![Coin::new(10, "uatom")],
+        })
+    );
+}
+
+#[test]
+fn test_dispatch_rewards_with_failing_swaps() {
+    let mut deps = mock_dependencies(&[
+        Coin::new(200, "uatom"),
+        Coin::new(3200, "uusd"),
+        Coin::new(6400, "usdr"),
+    ]);
+
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), default_init()).unwrap();
+
+    // the simulation of uusd fails
+    let msg = ExecuteMsg::SetSwapRoute {
+        denom: "uusd".to_string(),
+        swap_contract: MOCK_FAILING_SWAP_CONTRACT_ADDR.to_string(),
+        price: Decimal::percent(50),
+        max_spread: Decimal::percent(1),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // the pool returns half of the offer, less than the configured price allows
+    let msg = ExecuteMsg::SetSwapRoute {
+        denom: "usdr".to_string(),
+        swap_contract: MOCK_SWAP_CONTRACT_ADDR.to_string(),
+        price: Decimal::one(),
+        max_spread: Decimal::percent(1),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // both routes are skipped and the native rewards are still dispatched
+    let info = mock_info(MOCK_HUB_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {
+            validator_rewards: vec![],
+        },
+    )
+    .unwrap();
+    assert_eq!(2, res.messages.len());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_HUB_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&BondRewards {}).unwrap(),
+            funds: vec![Coin::new(190, "uatom")],
+        })
+    );
+    let skipped: Vec<String> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "swap_skipped")
+        .map(|a| a.value.clone())
+        .collect();
+    assert_eq!(
+        skipped,
+        vec!["3200uusd".to_string(), "6400usdr".to_string()]
+    );
+
+    // a swap failing on execution is reverted without failing the dispatch
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: ContractResult::Err("pool not found".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.attributes[0].value, "swap_failed");
+}
+
 #[test]
 fn test_update_config() {
     let mut deps = mock_dependencies(&[]);
@@ -136,11 +802,12 @@ fn test_update_config() {
     // check call from invalid owner
     let invalid_owner = String::from("invalid_owner");
     let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: Some(String::from("some_addr")),
-        hub_contract: None,
+        hub_contract: Some(String::from("some_addr")),
         statom_reward_denom: None,
-        lido_fee_address: None,
+        lido_fee_recipients: None,
         lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
     };
     let info = mock_info(&invalid_owner, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
@@ -148,15 +815,24 @@ fn test_update_config() {
 
     // change owner
     let new_owner = String::from("new_owner");
-    let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: Some(new_owner.clone()),
-        hub_contract: None,
-        statom_reward_denom: None,
-        lido_fee_address: None,
-        lido_fee_rate: None,
+    let propose_owner_msg = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.clone(),
+        expires_in: 100,
     };
     let info = mock_info(&owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
+    let res = execute(deps.as_mut(), mock_env(), info, propose_owner_msg);
+    assert!(res.is_ok());
+    let pending: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap()).unwrap();
+    assert_eq!(pending.pending_owner, Some(new_owner.clone()));
+
+    let info = mock_info(&new_owner, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
     assert!(res.is_ok());
 
     let config = CONFIG.load(&deps.storage).unwrap();
@@ -165,11 +841,12 @@ fn test_update_config() {
 
     // change hub_contract
     let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         hub_contract: Some(String::from("some_address")),
         statom_reward_denom: None,
-        lido_fee_address: None,
+        lido_fee_recipients: None,
         lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
     };
     let info = mock_info(&new_owner, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
@@ -185,11 +862,12 @@ fn test_update_config() {
 
     // change statom_reward_denom
     let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         hub_contract: None,
         statom_reward_denom: Some(String::from("new_denom")),
-        lido_fee_address: None,
+        lido_fee_recipients: None,
         lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
     };
     let info = mock_info(&new_owner, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
@@ -204,13 +882,17 @@ fn test_update_config() {
     let config = CONFIG.load(&deps.storage).unwrap();
     assert_eq!(String::from("uatom"), config.statom_reward_denom);
 
-    // change lido_fee_address
+    // change lido_fee_recipients
     let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         hub_contract: None,
         statom_reward_denom: None,
-        lido_fee_address: Some(String::from("some_address")),
+        lido_fee_recipients: Some(vec![LidoFeeRecipient {
+            address: String::from("some_address"),
+            weight: Decimal::one(),
+        }]),
         lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
     };
     let info = mock_info(&new_owner, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
@@ -218,19 +900,49 @@ fn test_update_config() {
 
     let config = CONFIG.load(&deps.storage).unwrap();
     assert_eq!(
-        deps.api
-            .addr_validate(&String::from("some_address"))
-            .unwrap(),
-        config.lido_fee_address
+        vec![FeeRecipient {
+            address: deps
+                .api
+                .addr_validate(&String::from("some_address"))
+                .unwrap(),
+            weight: Decimal::one(),
+        }],
+        config.lido_fee_recipients
+    );
+
+    // weights must sum to 1
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: Some(vec![
+            LidoFeeRecipient {
+                address: String::from("some_address"),
+                weight: Decimal::percent(50),
+            },
+            LidoFeeRecipient {
+                address: String::from("other_address"),
+                weight: Decimal::percent(40),
+            },
+        ]),
+        lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
+    };
+    let info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("lido fee recipients weights must sum to 1")
     );
 
     // change lido_fee_rate
     let update_config_msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         hub_contract: None,
         statom_reward_denom: None,
-        lido_fee_address: None,
+        lido_fee_recipients: None,
         lido_fee_rate: Some(Decimal::one()),
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
     };
     let info = mock_info(&new_owner, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, update_config_msg);
@@ -239,3 +951,128 @@ fn test_update_config() {
     let config = CONFIG.load(&deps.storage).unwrap();
     assert_eq!(Decimal::one(), config.lido_fee_rate);
 }
+
+#[test]
+fn test_fee_manager_role() {
+    let mut deps = mock_dependencies(&[]);
+
+    let owner = String::from("creator");
+    let fee_manager = String::from("fee_manager");
+    let info = mock_info(&owner, &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), default_init()).unwrap();
+
+    // only the owner can grant roles
+    let grant_role = ExecuteMsg::GrantRole {
+        role: Role::FeeManager,
+        address: fee_manager.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&fee_manager, &[]),
+        grant_role.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    execute(deps.as_mut(), mock_env(), info.clone(), grant_role).unwrap();
+
+    let roles: RolesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap()).unwrap();
+    let fee_managers = roles
+        .roles
+        .iter()
+        .find(|r| r.role == Role::FeeManager)
+        .unwrap();
+    assert_eq!(fee_managers.addresses, vec![fee_manager.clone()]);
+
+    // the fee manager can change the fees but not the hub contract
+    let update_fee = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: Some(Decimal::percent(10)),
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&fee_manager, &[]),
+        update_fee.clone(),
+    )
+    .unwrap();
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.lido_fee_rate, Decimal::percent(10));
+
+    let update_hub = ExecuteMsg::UpdateConfig {
+        hub_contract: Some(String::from("some_addr")),
+        statom_reward_denom: None,
+        lido_fee_recipients: None,
+        lido_fee_rate: None,
+        operator_fee_rate: None,
+        insurance_fee_rate: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&fee_manager, &[]),
+        update_hub,
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // a revoked fee manager can't change the fees anymore
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RevokeRole {
+            role: Role::FeeManager,
+            address: fee_manager.clone(),
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&fee_manager, &[]),
+        update_fee,
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+}
+
+#[test]
+fn test_migrate_legacy_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let legacy = LegacyConfig {
+        owner: Addr::unchecked("creator"),
+        hub_contract: Addr::unchecked(MOCK_HUB_CONTRACT_ADDR),
+        statom_reward_denom: "uatom".to_string(),
+        lido_fee_address: Addr::unchecked(MOCK_LIDO_FEE_ADDRESS),
+        lido_fee_rate: Decimal::percent(5),
+    };
+    LEGACY_CONFIG.save(&mut deps.storage, &legacy).unwrap();
+    assert!(CONFIG.load(&deps.storage).is_err());
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            owner: Addr::unchecked("creator"),
+            hub_contract: Addr::unchecked(MOCK_HUB_CONTRACT_ADDR),
+            statom_reward_denom: "uatom".to_string(),
+            lido_fee_recipients: vec![FeeRecipient {
+                address: Addr::unchecked(MOCK_LIDO_FEE_ADDRESS),
+                weight: Decimal::one(),
+            }],
+            lido_fee_rate: Decimal::percent(5),
+            operator_fee_rate: Decimal::zero(),
+            insurance_fee_rate: Decimal::zero(),
+        }
+    );
+
+    // migrating a current config leaves it untouched
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(CONFIG.load(&deps.storage).unwrap(), config);
+}
@@ -25,7 +25,7 @@ use cw20_base::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use crate::handler::*;
 use crate::msg::TokenInitMsg;
 use crate::state::HUB_CONTRACT;
-use basset::hub::is_paused;
+use basset::hub::{is_paused, PausableOperation};
 use cw20::MinterResponse;
 use cw20_base::ContractError;
 
@@ -65,8 +65,20 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // minting and burning are done by the hub, which checks its own bond and unbond pauses
     let hub_addr: Addr = HUB_CONTRACT.load(deps.storage)?;
-    if is_paused(deps.as_ref(), hub_addr.into_string())? {
+    let hub_operation = info.sender == hub_addr
+        && matches!(
+            msg,
+            ExecuteMsg::Mint { .. } | ExecuteMsg::Burn { .. } | ExecuteMsg::BurnFrom { .. }
+        );
+    if !hub_operation
+        && is_paused(
+            deps.as_ref(),
+            hub_addr.into_string(),
+            PausableOperation::TokenTransfers,
+        )?
+    {
         return Err(ContractError::Std(StdError::generic_err(
             "The contract is temporarily paused",
         )));
@@ -107,9 +119,9 @@ pub fn execute(
         } => execute_send_from(deps, env, info, owner, contract, amount, msg),
         ExecuteMsg::UpdateMarketing {
             project,
-            /// A longer description of the token and it's utility. Designed for tooltips or such
+            // A longer description of the token and it's utility. Designed for tooltips or such
             description,
-            /// The address (if any) who can update this data structure
+            // The address (if any) who can update this data structure
             marketing,
         } => execute_update_marketing(deps, env, info, project, description, marketing),
         ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
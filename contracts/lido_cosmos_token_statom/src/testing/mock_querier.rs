@@ -0,0 +1,105 @@
+// Copyright 2021 Lido
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use basset::hub::{Parameters, QueryMsg};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+
+pub const MOCK_HUB_CONTRACT_ADDR: &str = "hub";
+
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let contract_addr = String::from(MOCK_CONTRACT_ADDR);
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(&contract_addr, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    paused: bool,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return QuerierResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                });
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                if *contract_addr == MOCK_HUB_CONTRACT_ADDR
+                    && msg == &to_binary(&QueryMsg::Parameters {}).unwrap()
+                {
+                    let params = Parameters {
+                        epoch_period: 0,
+                        underlying_coin_denom: "uatom".to_string(),
+                        unbonding_period: 0,
+                        paused: Some(self.paused),
+                        liquidity_buffer_target: Default::default(),
+                        instant_unbond_fee: Default::default(),
+                        max_insurance_cover: Default::default(),
+                        min_bond_amount: Default::default(),
+                        min_total_supply: Default::default(),
+                        max_exchange_rate_increase: None,
+                        timelock_period: 0,
+                        paused_operations: 0,
+                        max_guardian_pause_duration: 0,
+                        guardian_pause_cooldown: 0,
+                        pause_info: None,
+                        operation_pauses: vec![],
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&params)))
+                } else {
+                    unimplemented!()
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            paused: false,
+        }
+    }
+
+    // pause or unpause the contracts on the hub
+    pub fn with_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2021 Lido
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{StdError, Uint128};
+use cw20::Cw20Coin;
+use cw20_base::msg::ExecuteMsg;
+use cw20_base::ContractError;
+
+use crate::contract::{execute, instantiate};
+use crate::msg::TokenInitMsg;
+use crate::testing::mock_querier::{mock_dependencies, MOCK_HUB_CONTRACT_ADDR};
+
+/// Covers if only the hub can burn stAtom while the contracts are paused.
+#[test]
+fn proper_burn_while_paused() {
+    let mut deps = mock_dependencies(&[]);
+    let holder = String::from("holder");
+
+    let msg = TokenInitMsg {
+        name: "stAtom".to_string(),
+        symbol: "STATOM".to_string(),
+        decimals: 6,
+        initial_balances: vec![
+            Cw20Coin {
+                address: holder.clone(),
+                amount: Uint128::from(100u64),
+            },
+            Cw20Coin {
+                address: MOCK_HUB_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(100u64),
+            },
+        ],
+        hub_contract: MOCK_HUB_CONTRACT_ADDR.to_string(),
+        marketing: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+    deps.querier.with_paused(true);
+    let burn = ExecuteMsg::Burn {
+        amount: Uint128::from(10u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&holder, &[]),
+        burn.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err("The contract is temporarily paused"))
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_HUB_CONTRACT_ADDR, &[]),
+        burn.clone(),
+    )
+    .unwrap();
+
+    deps.querier.with_paused(false);
+    execute(deps.as_mut(), mock_env(), mock_info(&holder, &[]), burn).unwrap();
+}
@@ -13,9 +13,13 @@
 // limitations under the License.
 
 use crate::registry::ValidatorResponse;
-use cosmwasm_std::{StdError, StdResult, Uint128};
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
 use std::ops::Sub;
 
+/// Splits amount_to_delegate so that every validator moves toward its weighted share of the
+/// total delegated amount without exceeding its max_delegation cap.
+/// Whatever can not be placed according to the weights (because of the caps) goes to the
+/// validators that still have room, in the order they are passed.
 pub fn calculate_delegations(
     mut amount_to_delegate: Uint128,
     validators: &[ValidatorResponse],
@@ -23,33 +27,50 @@ pub fn calculate_delegations(
     if validators.is_empty() {
         return Err(StdError::generic_err("Empty validators set"));
     }
+    let total_weight: u128 = validators.iter().map(|v| v.weight as u128).sum();
+    if total_weight == 0 {
+        return Err(StdError::generic_err(
+            "Validators set has no weight to delegate to",
+        ));
+    }
     let total_delegated: u128 = validators.iter().map(|v| v.total_delegated.u128()).sum();
     let total_coins_to_distribute = Uint128::from(total_delegated) + amount_to_delegate;
-    let coins_per_validator = total_coins_to_distribute.u128() / validators.len() as u128;
-    let remaining_coins = total_coins_to_distribute.u128() % validators.len() as u128;
+    let targets = calculate_targets(total_coins_to_distribute, validators);
 
     let mut delegations = vec![Uint128::zero(); validators.len()];
     for (index, validator) in validators.iter().enumerate() {
-        let extra_coin = if (index + 1) as u128 <= remaining_coins {
-            1u128
-        } else {
-            0u128
-        };
-        if coins_per_validator + extra_coin < validator.total_delegated.u128() {
+        let target = targets[index];
+        if target <= validator.total_delegated {
             continue;
         }
-        let mut to_delegate =
-            Uint128::from(coins_per_validator + extra_coin).sub(validator.total_delegated);
-        if to_delegate > amount_to_delegate {
-            to_delegate = amount_to_delegate
-        }
+        let to_delegate = target
+            .sub(validator.total_delegated)
+            .min(amount_to_delegate);
         delegations[index] = to_delegate;
         amount_to_delegate = amount_to_delegate.checked_sub(to_delegate)?;
         if amount_to_delegate.is_zero() {
             break;
         }
     }
-    // This is impossible but we have this check to prevent issues in case we do some changes in the distribution algorithm
+
+    // the caps did not allow to reach the weighted split, fill up validators with room left
+    for (index, validator) in validators.iter().enumerate() {
+        if amount_to_delegate.is_zero() {
+            break;
+        }
+        if validator.weight == 0 {
+            continue;
+        }
+        let delegated = validator.total_delegated + delegations[index];
+        let room = match validator.max_delegation {
+            Some(max_delegation) => max_delegation.saturating_sub(delegated),
+            None => amount_to_delegate,
+        };
+        let to_delegate = room.min(amount_to_delegate);
+        delegations[index] += to_delegate;
+        amount_to_delegate = amount_to_delegate.checked_sub(to_delegate)?;
+    }
+
     if amount_to_delegate.is_zero() {
         Ok(delegations)
     } else {
@@ -59,6 +80,76 @@ pub fn calculate_delegations(
     }
 }
 
+/// Returns the weighted share of total for every validator capped by its max_delegation.
+/// The rounding remainder goes one coin at a time to the first validators with non-zero weight
+pub fn calculate_targets(total: Uint128, validators: &[ValidatorResponse]) -> Vec<Uint128> {
+    let total_weight: u128 = validators.iter().map(|v| v.weight as u128).sum();
+    if total_weight == 0 {
+        return vec![Uint128::zero(); validators.len()];
+    }
+
+    let mut targets: Vec<Uint128> = validators
+        .iter()
+        .map(|v| total.multiply_ratio(v.weight as u128, total_weight))
+        .collect();
+    let mut remaining_coins = total.u128() - targets.iter().map(|t| t.u128()).sum::<u128>();
+
+    for (index, validator) in validators.iter().enumerate() {
+        if validator.weight > 0 && remaining_coins > 0 {
+            targets[index] += Uint128::from(1u128);
+            remaining_coins -= 1;
+        }
+        if let Some(max_delegation) = validator.max_delegation {
+            targets[index] = targets[index].min(max_delegation);
+        }
+    }
+    targets
+}
+
+/// Pairs validators delegated above their target with validators below it and returns
+/// at most max_moves (source index, destination index, amount) redelegations.
+/// Validators within tolerance of their target are left untouched, as well as the
+/// sources that can't redelegate their stake right now
+pub fn calculate_rebalance(
+    validators: &[ValidatorResponse],
+    can_redelegate: &[bool],
+    tolerance: Decimal,
+    max_moves: u32,
+) -> Vec<(usize, usize, Uint128)> {
+    let total_delegated: Uint128 = validators.iter().map(|v| v.total_delegated).sum();
+    let targets = calculate_targets(total_delegated, validators);
+
+    let mut surpluses: Vec<(usize, Uint128)> = vec![];
+    let mut deficits: Vec<(usize, Uint128)> = vec![];
+    for (index, validator) in validators.iter().enumerate() {
+        let target = targets[index];
+        let allowed_deviation = target * tolerance;
+        if validator.total_delegated > target + allowed_deviation && can_redelegate[index] {
+            surpluses.push((index, validator.total_delegated.sub(target)));
+        } else if validator.total_delegated + allowed_deviation < target {
+            deficits.push((index, target.sub(validator.total_delegated)));
+        }
+    }
+    surpluses.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+    deficits.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+
+    let mut moves: Vec<(usize, usize, Uint128)> = vec![];
+    let (mut src, mut dst) = (0, 0);
+    while src < surpluses.len() && dst < deficits.len() && moves.len() < max_moves as usize {
+        let amount = surpluses[src].1.min(deficits[dst].1);
+        moves.push((surpluses[src].0, deficits[dst].0, amount));
+        surpluses[src].1 = surpluses[src].1.sub(amount);
+        deficits[dst].1 = deficits[dst].1.sub(amount);
+        if surpluses[src].1.is_zero() {
+            src += 1;
+        }
+        if deficits[dst].1.is_zero() {
+            dst += 1;
+        }
+    }
+    moves
+}
+
 pub fn calculate_undelegations(
     mut undelegation_amount: Uint128,
     mut validators: Vec<ValidatorResponse>,
@@ -18,14 +18,25 @@ use std::collections::HashMap;
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    to_binary, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Attribute, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
-use crate::common::calculate_delegations;
+use crate::common::{calculate_delegations, calculate_rebalance};
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::registry::{Config, Validator, ValidatorResponse, CONFIG, REGISTRY};
+use crate::registry::{
+    default_rebalance_tolerance, Config, PendingRedelegation, Validator, ValidatorResponse,
+    ValidatorStatus, ValidatorStatusResponse, CONFIG, DEFAULT_WEIGHT, PENDING_REDELEGATIONS,
+    REGISTRY,
+};
+use basset::access_control::{
+    ensure_owner_or_role, grant_role, has_role, query_roles, revoke_role, Role,
+};
 use basset::hub::ExecuteMsg::{DispatchRewards, RedelegateProxy};
+use basset::hub::{is_paused, PausableOperation};
+use basset::ownership::{
+    accept_ownership, cancel_ownership_proposal, propose_new_owner, query_pending_owner,
+};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -39,11 +50,19 @@ pub fn instantiate(
         &Config {
             owner: info.sender,
             hub_contract: deps.api.addr_validate(msg.hub_contract.as_str())?,
+            max_commission: validate_max_commission(
+                msg.max_commission.unwrap_or_else(Decimal::one),
+            )?,
+            rebalance_tolerance: validate_rebalance_tolerance(
+                msg.rebalance_tolerance
+                    .unwrap_or_else(default_rebalance_tolerance),
+            )?,
         },
     )?;
 
     for v in msg.registry {
-        REGISTRY.save(deps.storage, v.address.as_str().as_bytes(), &v)?;
+        validate_validator(deps.as_ref(), &v)?;
+        REGISTRY.save(deps.storage, v.address.as_bytes(), &v)?;
     }
 
     Ok(Response::default())
@@ -53,22 +72,63 @@ pub fn instantiate(
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::AddValidator { validator } => add_validator(deps, env, info, validator),
+        ExecuteMsg::UpdateValidator { validator } => update_validator(deps, env, info, validator),
         ExecuteMsg::RemoveValidator { address } => remove_validator(deps, env, info, address),
+        ExecuteMsg::CancelValidatorRemoval { address } => {
+            cancel_validator_removal(deps, env, info, address)
+        }
         ExecuteMsg::UpdateConfig {
-            owner,
             hub_contract,
-        } => execute_update_config(deps, env, info, owner, hub_contract),
+            max_commission,
+            rebalance_tolerance,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            hub_contract,
+            max_commission,
+            rebalance_tolerance,
+        ),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let config = CONFIG.load(deps.storage)?;
+            propose_new_owner(deps, env, info, &config.owner, owner, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info, |deps, owner| {
+            CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+                config.owner = owner;
+                Ok(config)
+            })?;
+            Ok(())
+        }),
+        ExecuteMsg::CancelOwnershipProposal {} => {
+            let config = CONFIG.load(deps.storage)?;
+            cancel_ownership_proposal(deps, info, &config.owner)
+        }
+        ExecuteMsg::GrantRole { role, address } => {
+            let config = CONFIG.load(deps.storage)?;
+            grant_role(deps, info, &config.owner, role, address)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let config = CONFIG.load(deps.storage)?;
+            revoke_role(deps, info, &config.owner, role, address)
+        }
+        ExecuteMsg::CheckValidators {} => execute_check_validators(deps, env, info),
+        ExecuteMsg::Rebalance { max_moves } => execute_rebalance(deps, env, info, max_moves),
+        ExecuteMsg::ProcessPendingRedelegations {} => {
+            execute_process_pending_redelegations(deps, env, info)
+        }
     }
 }
 
-/// Update the config. Update the owner and hub contract address.
+/// Update the config. Update the hub contract address, max commission and rebalance tolerance.
 /// Only creator/owner is allowed to execute
 pub fn execute_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    owner: Option<String>,
     hub_contract: Option<String>,
+    max_commission: Option<Decimal>,
+    rebalance_tolerance: Option<Decimal>,
 ) -> StdResult<Response> {
     // only owner must be able to send this message.
     let config = CONFIG.load(deps.storage)?;
@@ -77,20 +137,29 @@ pub fn execute_update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    if let Some(o) = owner {
-        let owner_raw = deps.api.addr_validate(&o)?;
+    if let Some(hub) = hub_contract {
+        let hub_raw = deps.api.addr_validate(&hub)?;
 
         CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
-            last_config.owner = owner_raw;
+            last_config.hub_contract = hub_raw;
             Ok(last_config)
         })?;
     }
 
-    if let Some(hub) = hub_contract {
-        let hub_raw = deps.api.addr_validate(&hub)?;
+    if let Some(max_commission) = max_commission {
+        let max_commission = validate_max_commission(max_commission)?;
 
         CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
-            last_config.hub_contract = hub_raw;
+            last_config.max_commission = max_commission;
+            Ok(last_config)
+        })?;
+    }
+
+    if let Some(rebalance_tolerance) = rebalance_tolerance {
+        let rebalance_tolerance = validate_rebalance_tolerance(rebalance_tolerance)?;
+
+        CONFIG.update(deps.storage, |mut last_config| -> StdResult<_> {
+            last_config.rebalance_tolerance = rebalance_tolerance;
             Ok(last_config)
         })?;
     }
@@ -98,6 +167,24 @@ pub fn execute_update_config(
     Ok(Response::default())
 }
 
+fn validate_rebalance_tolerance(rebalance_tolerance: Decimal) -> StdResult<Decimal> {
+    if rebalance_tolerance > Decimal::one() {
+        return Err(StdError::generic_err(
+            "rebalance_tolerance must not be greater than 1",
+        ));
+    }
+    Ok(rebalance_tolerance)
+}
+
+fn validate_max_commission(max_commission: Decimal) -> StdResult<Decimal> {
+    if max_commission > Decimal::one() {
+        return Err(StdError::generic_err(
+            "max_commission must not be greater than 1",
+        ));
+    }
+    Ok(max_commission)
+}
+
 pub fn add_validator(
     deps: DepsMut,
     _env: Env,
@@ -107,31 +194,82 @@ pub fn add_validator(
     let config = CONFIG.load(deps.storage)?;
     let owner_address = config.owner;
     let hub_address = config.hub_contract;
-    if !(info.sender == owner_address || info.sender == hub_address) {
+    if !(info.sender == owner_address
+        || info.sender == hub_address
+        || has_role(deps.storage, Role::ValidatorManager, &info.sender))
+    {
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    REGISTRY.save(
+    if PENDING_REDELEGATIONS.has(deps.storage, validator.address.as_bytes()) {
+        return Err(StdError::generic_err("validator is being removed"));
+    }
+
+    validate_validator(deps.as_ref(), &validator)?;
+    REGISTRY.save(deps.storage, validator.address.as_bytes(), &validator)?;
+    Ok(Response::default())
+}
+
+/// Replaces a validator entry in the registry. A Removing validator stays Removing.
+/// Only owner or validator managers are allowed to execute
+pub fn update_validator(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    validator: Validator,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner_or_role(
         deps.storage,
-        validator.address.as_str().as_bytes(),
-        &validator,
+        &config.owner,
+        &info.sender,
+        Role::ValidatorManager,
     )?;
+
+    if !REGISTRY.has(deps.storage, validator.address.as_bytes()) {
+        return Err(StdError::generic_err("validator is not in the registry"));
+    }
+
+    validate_validator(deps.as_ref(), &validator)?;
+    REGISTRY.save(deps.storage, validator.address.as_bytes(), &validator)?;
     Ok(Response::default())
 }
 
+fn validate_validator(deps: Deps, validator: &Validator) -> StdResult<()> {
+    if let Some(reward_address) = &validator.reward_address {
+        deps.api.addr_validate(reward_address)?;
+    }
+    Ok(())
+}
+
 pub fn remove_validator(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     validator_address: String,
 ) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?;
-    let owner_address = config.owner;
-    if info.sender != owner_address {
-        return Err(StdError::generic_err("unauthorized"));
+    ensure_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &info.sender,
+        Role::ValidatorManager,
+    )?;
+
+    if !REGISTRY.has(deps.storage, validator_address.as_bytes()) {
+        return Err(StdError::generic_err("validator is not in the registry"));
     }
 
-    REGISTRY.remove(deps.storage, validator_address.as_str().as_bytes());
+    // the validator is marked as Removing and stops receiving new delegations,
+    // it stays in the registry until the hub stake is redelegated away
+    PENDING_REDELEGATIONS.save(
+        deps.storage,
+        validator_address.as_bytes(),
+        &PendingRedelegation {
+            validator: validator_address.clone(),
+            height: env.block.height,
+        },
+    )?;
 
     let mut validators = query_validators(deps.as_ref())?;
     if validators.is_empty() {
@@ -139,62 +277,328 @@ pub fn remove_validator(
             "Cannot remove the last validator in the registry",
         ));
     }
-    validators.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+    validators.sort_by_key(|v| v.total_delegated);
 
     let hub_address = config.hub_contract;
 
-    let query = deps
-        .querier
-        .query_delegation(hub_address.clone(), validator_address.clone());
+    let redelegation = redelegate_from(
+        deps.as_ref(),
+        &hub_address,
+        validator_address.clone(),
+        &mut validators,
+    )?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(msg) = redelegation.msg {
+        messages.push(msg);
+        messages.extend(dispatch_rewards_msg(deps.as_ref(), &hub_address)?);
+    }
+    // if there is another active redelegation from the validator the rest of the stake
+    // is redelegated later with ProcessPendingRedelegations
+    if redelegation.remaining.is_zero() {
+        finalize_removal(deps.storage, &validator_address);
+    }
+
+    let res = Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "remove_validator"),
+        attr("validator", validator_address),
+        attr("remaining", redelegation.remaining),
+    ]);
+    Ok(res)
+}
 
+/// Stops the removal of a Removing validator, the stake left on it doesn't need to be moved anymore.
+/// Only owner or validator managers are allowed to execute
+pub fn cancel_validator_removal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    validator_address: String,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &info.sender,
+        Role::ValidatorManager,
+    )?;
+
+    if !PENDING_REDELEGATIONS.has(deps.storage, validator_address.as_bytes()) {
+        return Err(StdError::generic_err("validator is not being removed"));
+    }
+    PENDING_REDELEGATIONS.remove(deps.storage, validator_address.as_bytes());
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_validator_removal"),
+        attr("validator", validator_address),
+    ]))
+}
+
+/// Retries redelegations postponed by remove_validator.
+/// Entries are removed from the queue once the stake is redelegated or there is nothing left.
+/// Permissionless
+pub fn execute_process_pending_redelegations(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let hub_address = config.hub_contract;
+
+    let pending = query_pending_redelegations(deps.as_ref())?;
+    let mut validators = query_validators(deps.as_ref())?;
+    if validators.is_empty() {
+        return Err(StdError::generic_err("Empty validators set"));
+    }
+    validators.sort_by_key(|v| v.total_delegated);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attrs: Vec<Attribute> = vec![attr("action", "process_pending_redelegations")];
+    for p in pending {
+        let redelegation = redelegate_from(
+            deps.as_ref(),
+            &hub_address,
+            p.validator.clone(),
+            &mut validators,
+        )?;
+        if let Some(msg) = redelegation.msg {
+            messages.push(msg);
+            attrs.push(attr("redelegated_from", p.validator.clone()));
+        }
+        if redelegation.remaining.is_zero() {
+            finalize_removal(deps.storage, &p.validator);
+            attrs.push(attr("removed_validator", p.validator));
+        }
+    }
+
+    if !messages.is_empty() {
+        messages.extend(dispatch_rewards_msg(deps.as_ref(), &hub_address)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(attrs))
+}
+
+/// Removes a drained validator from the registry
+fn finalize_removal(storage: &mut dyn Storage, validator_address: &str) {
+    REGISTRY.remove(storage, validator_address.as_bytes());
+    PENDING_REDELEGATIONS.remove(storage, validator_address.as_bytes());
+}
+
+/// Redelegates the hub stake away from validators that are out of the active set
+/// (jailed, tombstoned or unbonding) or charge more than max_commission.
+/// Permissionless
+pub fn execute_check_validators(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let hub_address = config.hub_contract.clone();
+
+    let mut validators = query_validators(deps.as_ref())?;
+    validators.sort_by_key(|v| v.total_delegated);
+
+    let bonded = deps.querier.query_all_validators()?;
     let mut messages: Vec<CosmosMsg> = vec![];
-    if let Ok(q) = query {
-        let delegated_amount = q;
-
-        let mut redelegations: Vec<(String, Coin)> = vec![];
-        if let Some(delegation) = delegated_amount {
-            // Terra core returns zero if there is another active redelegation
-            // That means we cannot start a new redelegation, so we only remove a validator from
-            // the registry.
-            // We'll do a redelegation manually later by sending RedelegateProxy to the hub
-            if delegation.can_redelegate.amount < delegation.amount.amount {
-                return StdResult::Ok(Response::new());
-            }
-
-            let delegations =
-                calculate_delegations(delegation.amount.amount, validators.as_slice())?;
-
-            for i in 0..delegations.len() {
-                if delegations[i].is_zero() {
-                    continue;
-                }
-                redelegations.push((
-                    validators[i].address.clone(),
-                    Coin::new(delegations[i].u128(), delegation.amount.denom.as_str()),
-                ));
-            }
-
-            let regelegate_msg = RedelegateProxy {
-                src_validator: validator_address,
+    let mut attrs: Vec<Attribute> = vec![attr("action", "check_validators")];
+    for item in REGISTRY.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        let validator = item?.1;
+        if is_healthy(&config, &bonded, &validator.address) {
+            continue;
+        }
+        attrs.push(attr("unhealthy_validator", validator.address.clone()));
+        if validators.is_empty() {
+            continue;
+        }
+        if let Some(msg) = redelegate_from(
+            deps.as_ref(),
+            &hub_address,
+            validator.address,
+            &mut validators,
+        )?
+        .msg
+        {
+            messages.push(msg);
+        }
+    }
+
+    if !messages.is_empty() {
+        messages.extend(dispatch_rewards_msg(deps.as_ref(), &hub_address)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(attrs))
+}
+
+/// Moves stake from validators delegated above their target to the ones below it.
+/// Validators with an active redelegation are not used as a source.
+/// Permissionless
+pub fn execute_rebalance(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    max_moves: u32,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let hub_address = config.hub_contract.clone();
+
+    let validators = query_validators(deps.as_ref())?;
+    let mut can_redelegate: Vec<bool> = vec![];
+    for validator in validators.iter() {
+        if validator.total_delegated.is_zero() {
+            can_redelegate.push(false);
+            continue;
+        }
+        // Terra core returns zero if there is another active redelegation
+        let delegation = deps
+            .querier
+            .query_delegation(hub_address.clone(), validator.address.clone())?;
+        can_redelegate.push(match delegation {
+            Some(d) => d.can_redelegate.amount >= d.amount.amount,
+            None => false,
+        });
+    }
+
+    let moves = calculate_rebalance(
+        &validators,
+        &can_redelegate,
+        config.rebalance_tolerance,
+        max_moves,
+    );
+    let denom = deps.querier.query_bonded_denom()?;
+
+    // group the moves by source validator, the hub redelegates from a single validator per message
+    let mut redelegations: Vec<(usize, Vec<(String, Coin)>)> = vec![];
+    for (src, dst, amount) in moves.iter() {
+        let redelegation = (
+            validators[*dst].address.clone(),
+            Coin::new(amount.u128(), denom.as_str()),
+        );
+        match redelegations.iter_mut().find(|(s, _)| s == src) {
+            Some((_, r)) => r.push(redelegation),
+            None => redelegations.push((*src, vec![redelegation])),
+        }
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (src, redelegations) in redelegations {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: validators[src].address.clone(),
                 redelegations,
-            };
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: hub_address.clone().into_string(),
-                msg: to_binary(&regelegate_msg)?,
-                funds: vec![],
-            }));
+            })?,
+            funds: vec![],
+        }));
+    }
+    if !messages.is_empty() {
+        messages.extend(dispatch_rewards_msg(deps.as_ref(), &hub_address)?);
+    }
 
-            let msg = DispatchRewards {};
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: hub_address.into_string(),
-                msg: to_binary(&msg)?,
-                funds: vec![],
-            }));
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "rebalance"),
+        attr("moves", moves.len().to_string()),
+    ]))
+}
+
+/// Outcome of an attempt to move the hub stake away from a validator
+struct Redelegation {
+    /// RedelegateProxy message moving the stake that can be redelegated now
+    msg: Option<CosmosMsg>,
+    /// Stake left on the validator after the redelegation
+    remaining: Uint128,
+}
+
+/// Builds a RedelegateProxy message moving as much of the hub delegation of src_validator
+/// as possible to validators and accounts the moved stake in their total_delegated
+fn redelegate_from(
+    deps: Deps,
+    hub_address: &Addr,
+    src_validator: String,
+    validators: &mut [ValidatorResponse],
+) -> StdResult<Redelegation> {
+    // query_delegation() fails if there is no delegation pair on Terra core,
+    // see the comment in query_validators
+    let delegated = deps
+        .querier
+        .query_all_delegations(hub_address)?
+        .into_iter()
+        .find(|d| d.validator == src_validator)
+        .map(|d| d.amount.amount)
+        .unwrap_or_default();
+    if delegated.is_zero() {
+        return Ok(Redelegation {
+            msg: None,
+            remaining: Uint128::zero(),
+        });
+    }
+
+    let delegation = deps
+        .querier
+        .query_delegation(hub_address.clone(), src_validator.clone())?
+        .ok_or_else(|| StdError::generic_err("delegation not found"))?;
+    // Terra core returns zero if there is another active redelegation
+    // That means we can redelegate only the part that is not locked by it
+    let amount = delegation
+        .can_redelegate
+        .amount
+        .min(delegation.amount.amount);
+    let remaining = delegation.amount.amount.checked_sub(amount)?;
+    if amount.is_zero() {
+        return Ok(Redelegation {
+            msg: None,
+            remaining,
+        });
+    }
+
+    let delegations = calculate_delegations(amount, validators)?;
+
+    let mut redelegations: Vec<(String, Coin)> = vec![];
+    for i in 0..delegations.len() {
+        if delegations[i].is_zero() {
+            continue;
         }
+        validators[i].total_delegated += delegations[i];
+        redelegations.push((
+            validators[i].address.clone(),
+            Coin::new(delegations[i].u128(), delegation.amount.denom.as_str()),
+        ));
     }
 
-    let res = Response::new().add_messages(messages);
-    Ok(res)
+    let regelegate_msg = RedelegateProxy {
+        src_validator,
+        redelegations,
+    };
+    Ok(Redelegation {
+        msg: Some(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_address.to_string(),
+            msg: to_binary(&regelegate_msg)?,
+            funds: vec![],
+        })),
+        remaining,
+    })
+}
+
+/// Returns the DispatchRewards message of the hub, none if the hub's rewards are paused,
+/// so the pause doesn't block the redelegations
+fn dispatch_rewards_msg(deps: Deps, hub_address: &Addr) -> StdResult<Option<CosmosMsg>> {
+    if is_paused(deps, hub_address.to_string(), PausableOperation::Rewards)? {
+        return Ok(None);
+    }
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: hub_address.to_string(),
+        msg: to_binary(&DispatchRewards {})?,
+        funds: vec![],
+    })))
+}
+
+/// A validator is healthy if it is in the bonded set and its commission
+/// does not exceed max_commission.
+/// The Validator staking query returns validators in any status, so the membership
+/// is checked in the bonded set returned by AllValidators instead. Jailed, tombstoned
+/// and unbonding validators are not in it and are treated as unhealthy
+fn is_healthy(config: &Config, bonded: &[cosmwasm_std::Validator], address: &str) -> bool {
+    bonded
+        .iter()
+        .any(|v| v.address == address && v.commission <= config.max_commission)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -202,11 +606,59 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetValidatorsForDelegation {} => {
             let mut validators = query_validators(deps)?;
-            validators.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+            validators.sort_by_key(|v| v.total_delegated);
             to_binary(&validators)
         }
+        QueryMsg::GetValidators {} => to_binary(&query_registry(deps)?),
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingRedelegations {} => to_binary(&query_pending_redelegations(deps)?),
+        QueryMsg::ValidatorsStatus {} => to_binary(&query_validators_status(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::Roles {} => to_binary(&query_roles(deps)?),
+    }
+}
+
+fn query_registry(deps: Deps) -> StdResult<Vec<Validator>> {
+    REGISTRY
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+fn query_pending_redelegations(deps: Deps) -> StdResult<Vec<PendingRedelegation>> {
+    PENDING_REDELEGATIONS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+fn query_validators_status(deps: Deps) -> StdResult<Vec<ValidatorStatusResponse>> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut delegations = HashMap::new();
+    for delegation in deps.querier.query_all_delegations(&config.hub_contract)? {
+        delegations.insert(delegation.validator, delegation.amount.amount);
+    }
+
+    let mut validators: Vec<ValidatorStatusResponse> = vec![];
+    for item in REGISTRY.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        let validator = item?.1;
+        let status = if PENDING_REDELEGATIONS.has(deps.storage, validator.address.as_bytes()) {
+            ValidatorStatus::Removing
+        } else if validator.active {
+            ValidatorStatus::Active
+        } else {
+            ValidatorStatus::Inactive
+        };
+        validators.push(ValidatorStatusResponse {
+            total_delegated: *delegations
+                .get(&validator.address)
+                .unwrap_or(&Uint128::zero()),
+            address: validator.address,
+            status,
+        });
     }
+    Ok(validators)
 }
 
 fn query_config(deps: Deps) -> StdResult<Config> {
@@ -216,18 +668,28 @@ fn query_config(deps: Deps) -> StdResult<Config> {
 
 fn query_validators(deps: Deps) -> StdResult<Vec<ValidatorResponse>> {
     let config = CONFIG.load(deps.storage)?;
-    let hub_address = config.hub_contract;
+    let hub_address = config.hub_contract.clone();
 
     let mut delegations = HashMap::new();
     for delegation in deps.querier.query_all_delegations(&hub_address)? {
         delegations.insert(delegation.validator, delegation.amount.amount);
     }
 
+    let bonded = deps.querier.query_all_validators()?;
     let mut validators: Vec<ValidatorResponse> = vec![];
     for item in REGISTRY.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        let registry_validator = item?.1;
+        if !registry_validator.active
+            || PENDING_REDELEGATIONS.has(deps.storage, registry_validator.address.as_bytes())
+            || !is_healthy(&config, &bonded, &registry_validator.address)
+        {
+            continue;
+        }
         let mut validator = ValidatorResponse {
             total_delegated: Default::default(),
-            address: item?.1.address,
+            address: registry_validator.address,
+            weight: registry_validator.weight.unwrap_or(DEFAULT_WEIGHT),
+            max_delegation: registry_validator.max_delegation,
         };
         // TODO: check that cosmos cosmwasm module has this bug or not
         // There is a bug in terra/core.
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use crate::registry::Validator;
+use basset::access_control::Role;
+use cosmwasm_std::Decimal;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -20,30 +22,85 @@ use serde::{Deserialize, Serialize};
 pub struct InstantiateMsg {
     pub registry: Vec<Validator>,
     pub hub_contract: String,
+    /// Maximum commission of validators allowed to receive delegations, 1 if not set
+    #[serde(default)]
+    pub max_commission: Option<Decimal>,
+    /// Allowed relative deviation from the target delegation, 1% if not set
+    #[serde(default)]
+    pub rebalance_tolerance: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    /// Adds a validator to the registry
+    /// Adds a validator to the registry. A Removing validator can't be added back
     AddValidator { validator: Validator },
 
-    /// Remove a validator from the registry
+    /// Replaces metadata, weight and cap of a validator already in the registry.
+    /// A Removing validator stays Removing
+    UpdateValidator { validator: Validator },
+
+    /// Marks a validator as Removing and redelegates the hub stake away from it.
+    /// The validator is removed from the registry once it has no stake left
     RemoveValidator { address: String },
 
+    /// Cancels the removal of a Removing validator, which receives delegations again
+    CancelValidatorRemoval { address: String },
+
     /// Update config
     UpdateConfig {
-        owner: Option<String>,
         hub_contract: Option<String>,
+        max_commission: Option<Decimal>,
+        rebalance_tolerance: Option<Decimal>,
     },
+
+    /// Proposes a new owner, who has to accept the ownership within `expires_in` seconds
+    ProposeNewOwner { owner: String, expires_in: u64 },
+
+    /// Accepts the proposed ownership. Only the proposed owner can execute
+    AcceptOwnership {},
+
+    /// Cancels the pending ownership proposal
+    CancelOwnershipProposal {},
+
+    /// Grants the role to the address
+    GrantRole { role: Role, address: String },
+
+    /// Revokes the role from the address
+    RevokeRole { role: Role, address: String },
+
+    /// Redelegates stake away from validators that left the active set
+    /// or charge more than max_commission.
+    /// Permissionless
+    CheckValidators {},
+
+    /// Redelegates stake from validators above their target delegation to the ones below it,
+    /// making at most max_moves redelegations.
+    /// Permissionless
+    Rebalance { max_moves: u32 },
+
+    /// Retries redelegations from Removing validators postponed because of an active redelegation
+    /// and removes the drained ones from the registry.
+    /// Permissionless
+    ProcessPendingRedelegations {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // GetValidatorsForDelegation returns validators sorted by available amount for delegation (delegation_limit - total_delegated)
+    // GetValidatorsForDelegation returns active validators sorted by available amount for delegation (delegation_limit - total_delegated)
     GetValidatorsForDelegation {},
+    // GetValidators returns all validators from the registry
+    GetValidators {},
     Config {},
+    // PendingRedelegations returns validators being removed whose stake is waiting to be redelegated
+    PendingRedelegations {},
+    // ValidatorsStatus returns the status and the hub stake of every validator in the registry
+    ValidatorsStatus {},
+    // PendingOwner returns the proposed owner who hasn't accepted the ownership yet
+    PendingOwner {},
+    // Roles returns the addresses granted each role
+    Roles {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
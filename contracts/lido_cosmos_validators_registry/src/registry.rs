@@ -15,23 +15,68 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
 use cosmwasm_std::Uint128;
+use cosmwasm_std::{Addr, Decimal};
 use cw_storage_plus::{Item, Map};
 
 pub static CONFIG: Item<Config> = Item::new("config");
 
 pub static REGISTRY: Map<&[u8], Validator> = Map::new("validators_registry");
 
+/// Validators being removed from the registry. A validator stays in REGISTRY with the Removing
+/// status until the hub stake is redelegated away from it
+pub static PENDING_REDELEGATIONS: Map<&[u8], PendingRedelegation> =
+    Map::new("pending_redelegations");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: Addr,
     pub hub_contract: Addr,
+    /// Validators charging a higher commission are excluded from delegation
+    #[serde(default = "default_max_commission")]
+    pub max_commission: Decimal,
+    /// Allowed relative deviation of a validator delegation from its target before Rebalance moves stake
+    #[serde(default = "default_rebalance_tolerance")]
+    pub rebalance_tolerance: Decimal,
+}
+
+fn default_max_commission() -> Decimal {
+    Decimal::one()
+}
+
+pub fn default_rebalance_tolerance() -> Decimal {
+    Decimal::percent(1)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Validator {
     pub address: String,
+    /// Address that receives the operator share of the rewards produced by the validator
+    #[serde(default)]
+    pub reward_address: Option<String>,
+    /// Operator name of the validator
+    #[serde(default)]
+    pub moniker: Option<String>,
+    /// Relative weight used to split delegations between validators, 1 if not set.
+    /// Validators with zero weight receive no new delegations
+    #[serde(default)]
+    pub weight: Option<u64>,
+    /// Maximum amount the hub is allowed to delegate to the validator
+    #[serde(default)]
+    pub max_delegation: Option<Uint128>,
+    /// Inactive validators stay in the registry but receive no new delegations
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+pub const DEFAULT_WEIGHT: u64 = 1;
+
+fn default_weight() -> u64 {
+    DEFAULT_WEIGHT
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -40,4 +85,36 @@ pub struct ValidatorResponse {
     pub total_delegated: Uint128,
 
     pub address: String,
+
+    #[serde(default = "default_weight")]
+    pub weight: u64,
+
+    #[serde(default)]
+    pub max_delegation: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRedelegation {
+    /// Validator still holding the hub stake
+    pub validator: String,
+    /// Height of the block the removal was requested at
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    Active,
+    /// Kept in the registry but receives no new delegations
+    Inactive,
+    /// Draining, removed from the registry once the hub has no stake on it
+    Removing,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorStatusResponse {
+    pub address: String,
+    pub status: ValidatorStatus,
+    /// Stake the hub has on the validator, for Removing validators it's what is left to drain
+    pub total_delegated: Uint128,
 }
@@ -13,14 +13,15 @@
 // limitations under the License.
 
 use crate::registry::ValidatorResponse as RegistryValidator;
+use basset::hub::{Parameters, PausableOperation, QueryMsg as HubQueryMsg};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
 use cosmwasm_std::{
     from_slice, to_binary, Coin, ContractResult, CustomQuery, FullDelegation, OwnedDeps, Querier,
-    QuerierResult, QueryRequest, SystemError, Uint128, Validator, WasmQuery,
+    QuerierResult, QueryRequest, StakingQuery, SystemError, Validator, ValidatorResponse,
+    WasmQuery,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 pub const MOCK_CONTRACT_ADDR: &str = "cosmos2contract";
 
@@ -48,6 +49,10 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<CustomQueryWrapper>,
     validators: Vec<RegistryValidator>,
+    // out of the bonded set, returned by the Validator query but not by AllValidators
+    unbonded_validators: Vec<Validator>,
+    // operations paused on the hub
+    paused_operations: u8,
 }
 
 impl Querier for WasmMockQuerier {
@@ -71,12 +76,47 @@ impl WasmMockQuerier {
         match &request {
             QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: _,
-                msg: _,
+                msg,
             }) => {
+                if msg == &to_binary(&HubQueryMsg::Parameters {}).unwrap() {
+                    let params = Parameters {
+                        epoch_period: 0,
+                        underlying_coin_denom: "uatom".to_string(),
+                        unbonding_period: 0,
+                        paused: None,
+                        liquidity_buffer_target: Default::default(),
+                        instant_unbond_fee: Default::default(),
+                        max_insurance_cover: Default::default(),
+                        min_bond_amount: Default::default(),
+                        min_total_supply: Default::default(),
+                        max_exchange_rate_increase: None,
+                        timelock_period: 0,
+                        paused_operations: self.paused_operations,
+                        max_guardian_pause_duration: 0,
+                        guardian_pause_cooldown: 0,
+                        pause_info: None,
+                        operation_pauses: vec![],
+                    };
+                    return QuerierResult::Ok(ContractResult::from(to_binary(&params)));
+                }
                 let mut validators = self.validators.clone();
-                validators.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+                validators.sort_by_key(|v| v.total_delegated);
                 QuerierResult::Ok(ContractResult::from(to_binary(&validators)))
             }
+            QueryRequest::Staking(StakingQuery::Validator { address }) => {
+                match self
+                    .unbonded_validators
+                    .iter()
+                    .find(|v| v.address == *address)
+                {
+                    Some(validator) => {
+                        QuerierResult::Ok(ContractResult::from(to_binary(&ValidatorResponse {
+                            validator: Some(validator.clone()),
+                        })))
+                    }
+                    None => self.base.handle_query(request),
+                }
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -88,16 +128,12 @@ impl WasmMockQuerier {
     ) {
         self.base.update_staking(denom, validators, delegations);
     }
-}
-
-#[derive(Clone, Default)]
-pub struct BalanceQuerier {
-    _balances: HashMap<String, Coin>,
-}
-
-#[derive(Clone, Default)]
-pub struct TokenQuerier {
-    _balances: HashMap<String, HashMap<String, Uint128>>,
+    pub fn update_unbonded_validators(&mut self, validators: &[Validator]) {
+        self.unbonded_validators = validators.to_vec();
+    }
+    pub fn pause_hub_operation(&mut self, operation: PausableOperation) {
+        self.paused_operations |= operation.mask();
+    }
 }
 
 impl WasmMockQuerier {
@@ -105,6 +141,8 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             validators: vec![],
+            unbonded_validators: vec![],
+            paused_operations: 0,
         }
     }
 }
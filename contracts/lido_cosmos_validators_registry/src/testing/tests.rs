@@ -10,16 +10,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::{calculate_delegations, calculate_undelegations};
-use crate::contract::{execute, instantiate};
-use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::registry::{Validator, ValidatorResponse, CONFIG, REGISTRY};
+use crate::common::{calculate_delegations, calculate_rebalance, calculate_undelegations};
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::registry::{
+    PendingRedelegation, Validator, ValidatorResponse, ValidatorStatus, ValidatorStatusResponse,
+    CONFIG, DEFAULT_WEIGHT, PENDING_REDELEGATIONS, REGISTRY,
+};
 use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use basset::access_control::Role;
 use basset::hub::ExecuteMsg::{DispatchRewards, RedelegateProxy};
+use basset::hub::PausableOperation;
+use basset::ownership::PendingOwnerResponse;
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    coin, coins, to_binary, Addr, Api, Coin, CosmosMsg, FullDelegation, StdError, Uint128,
-    Validator as CosmosValidator, WasmMsg,
+    coin, coins, from_binary, to_binary, Addr, Api, Coin, CosmosMsg, Decimal, FullDelegation,
+    StdError, Uint128, Validator as CosmosValidator, WasmMsg,
 };
 
 #[test]
@@ -31,8 +37,15 @@ fn proper_instantiate() {
     let msg = InstantiateMsg {
         registry: vec![Validator {
             address: Default::default(),
+            reward_address: None,
+            moniker: None,
+            weight: None,
+            max_delegation: None,
+            active: true,
         }],
         hub_contract: hub_address.clone(),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let info = mock_info("creator", &coins(1000, "earth"));
 
@@ -53,12 +66,19 @@ fn add_validator() {
     let msg = InstantiateMsg {
         registry: vec![],
         hub_contract: String::from("hub_contract_address"),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let info = mock_info("creator", &coins(2, "token"));
     let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
     let validator = Validator {
         address: Default::default(),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let msg = ExecuteMsg::AddValidator {
@@ -69,7 +89,7 @@ fn add_validator() {
     match _res {
         Ok(_) => {
             let v = REGISTRY
-                .load(&deps.storage, validator.address.as_str().as_bytes())
+                .load(&deps.storage, validator.address.as_bytes())
                 .unwrap();
             assert_eq!(validator, v);
         }
@@ -77,6 +97,261 @@ fn add_validator() {
     }
 }
 
+#[test]
+fn update_validator() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+
+    let validator = Validator {
+        address: String::from("validator1"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let msg = InstantiateMsg {
+        registry: vec![validator.clone()],
+        hub_contract: String::from("hub_contract_address"),
+        max_commission: None,
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let updated_validator = Validator {
+        moniker: Some(String::from("Validator One")),
+        weight: Some(3),
+        max_delegation: Some(Uint128::from(1000u128)),
+        active: false,
+        ..validator
+    };
+
+    let msg = ExecuteMsg::UpdateValidator {
+        validator: updated_validator.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("villain", &[]), msg);
+    assert_eq!(res.err().unwrap(), StdError::generic_err("unauthorized"));
+
+    let msg = ExecuteMsg::UpdateValidator {
+        validator: Validator {
+            address: String::from("unknown"),
+            ..updated_validator.clone()
+        },
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.err().unwrap(),
+        StdError::generic_err("validator is not in the registry")
+    );
+
+    let msg = ExecuteMsg::UpdateValidator {
+        validator: updated_validator.clone(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let v = REGISTRY
+        .load(&deps.storage, updated_validator.address.as_bytes())
+        .unwrap();
+    assert_eq!(updated_validator, v);
+
+    // inactive validators are not returned for delegation
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetValidatorsForDelegation {},
+    )
+    .unwrap();
+    let validators: Vec<ValidatorResponse> = from_binary(&res).unwrap();
+    assert!(validators.is_empty());
+}
+
+#[test]
+fn update_removing_validator() {
+    let mut deps = mock_dependencies(&[]);
+    let hub_contract_address = Addr::unchecked("hub_contract_address");
+
+    let validator = |address: &str| Validator {
+        address: String::from(address),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let validator1 = validator("validator1");
+    let validator2 = validator("validator2");
+    let cosmos_validator = |address: &str| CosmosValidator {
+        address: String::from(address),
+        commission: Decimal::zero(),
+        max_commission: Default::default(),
+        max_change_rate: Default::default(),
+    };
+
+    // the stake of validator1 can't be redelegated yet
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            FullDelegation {
+                validator: validator1.address.clone(),
+                delegator: hub_contract_address.clone(),
+                amount: coin(110, "uatom"),
+                can_redelegate: coin(0, "uatom"),
+                accumulated_rewards: vec![],
+            },
+            sample_delegation(
+                hub_contract_address.clone(),
+                validator2.address.clone(),
+                coin(110, "uatom"),
+            ),
+        ],
+        &[
+            cosmos_validator("validator1"),
+            cosmos_validator("validator2"),
+        ],
+    );
+
+    let msg = InstantiateMsg {
+        registry: vec![validator1.clone(), validator2],
+        hub_contract: hub_contract_address.to_string(),
+        max_commission: None,
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RemoveValidator {
+        address: validator1.address.clone(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert!(PENDING_REDELEGATIONS.has(&deps.storage, validator1.address.as_bytes()));
+
+    // updating the metadata doesn't cancel the removal
+    let updated_validator = Validator {
+        moniker: Some(String::from("Validator One")),
+        ..validator1.clone()
+    };
+    let msg = ExecuteMsg::UpdateValidator {
+        validator: updated_validator.clone(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        REGISTRY
+            .load(&deps.storage, validator1.address.as_bytes())
+            .unwrap(),
+        updated_validator
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidatorsStatus {}).unwrap();
+    let statuses: Vec<ValidatorStatusResponse> = from_binary(&res).unwrap();
+    assert_eq!(statuses[0].status, ValidatorStatus::Removing);
+
+    // nor does adding it again
+    let msg = ExecuteMsg::AddValidator {
+        validator: validator1.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.err().unwrap(),
+        StdError::generic_err("validator is being removed")
+    );
+
+    // the removal is cancelled explicitly
+    let msg = ExecuteMsg::CancelValidatorRemoval {
+        address: validator1.address.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("villain", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.err().unwrap(), StdError::generic_err("unauthorized"));
+
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert!(!PENDING_REDELEGATIONS.has(&deps.storage, validator1.address.as_bytes()));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidatorsStatus {}).unwrap();
+    let statuses: Vec<ValidatorStatusResponse> = from_binary(&res).unwrap();
+    assert_eq!(statuses[0].status, ValidatorStatus::Active);
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.err().unwrap(),
+        StdError::generic_err("validator is not being removed")
+    );
+}
+
+#[test]
+fn validator_manager_role() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+
+    let msg = InstantiateMsg {
+        registry: vec![],
+        hub_contract: String::from("hub_contract_address"),
+        max_commission: None,
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let manager = mock_info("validator_manager", &[]);
+    let mut validator = Validator {
+        address: String::from("validator"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let add_validator = ExecuteMsg::AddValidator {
+        validator: validator.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        manager.clone(),
+        add_validator.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::GrantRole {
+            role: Role::ValidatorManager,
+            address: manager.sender.to_string(),
+        },
+    )
+    .unwrap();
+
+    // the validator manager can manage validators but not the config
+    execute(deps.as_mut(), mock_env(), manager.clone(), add_validator).unwrap();
+    validator.weight = Some(2);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        manager.clone(),
+        ExecuteMsg::UpdateValidator {
+            validator: validator.clone(),
+        },
+    )
+    .unwrap();
+    let v = REGISTRY
+        .load(&deps.storage, validator.address.as_bytes())
+        .unwrap();
+    assert_eq!(validator, v);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        manager,
+        ExecuteMsg::UpdateConfig {
+            hub_contract: None,
+            max_commission: Some(Decimal::percent(5)),
+            rebalance_tolerance: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+}
+
 #[test]
 fn ownership_tests() {
     let mut deps = mock_dependencies(&coins(2, "token"));
@@ -84,6 +359,8 @@ fn ownership_tests() {
     let msg = InstantiateMsg {
         registry: vec![],
         hub_contract: String::from("hub_contract_address"),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let info = mock_info("creator", &coins(2, "token"));
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -92,6 +369,11 @@ fn ownership_tests() {
 
     let validator = Validator {
         address: Default::default(),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let msg = ExecuteMsg::AddValidator {
@@ -108,7 +390,8 @@ fn ownership_tests() {
 
     let msg = ExecuteMsg::UpdateConfig {
         hub_contract: None,
-        owner: None,
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     assert_eq!(res.err().unwrap(), StdError::generic_err("unauthorized"));
@@ -121,6 +404,8 @@ fn update_config() {
     let msg = InstantiateMsg {
         registry: vec![],
         hub_contract: String::from("hub_contract_address"),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let info = mock_info("creator", &coins(2, "token"));
     let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -128,7 +413,8 @@ fn update_config() {
     let new_hub_address = String::from("new_hub_contract");
     let msg = ExecuteMsg::UpdateConfig {
         hub_contract: Some(new_hub_address.clone()),
-        owner: None,
+        max_commission: None,
+        rebalance_tolerance: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     assert!(res.is_ok());
@@ -138,13 +424,25 @@ fn update_config() {
         config.hub_contract
     );
 
+    // the owner changes once the proposed owner accepts
     let new_owner = String::from("new_owner");
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some(new_owner.clone()),
-        hub_contract: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.clone(),
+        expires_in: 100,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     assert!(res.is_ok());
+    let pending: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap()).unwrap();
+    assert_eq!(pending.pending_owner, Some(new_owner.clone()));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&new_owner, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert!(res.is_ok());
     let config = CONFIG.load(&deps.storage).unwrap();
     assert_eq!(deps.api.addr_validate(&new_owner).unwrap(), config.owner);
 }
@@ -158,18 +456,38 @@ fn remove_validator() {
         .unwrap();
     let validator1 = Validator {
         address: String::from("validator"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let validator2 = Validator {
         address: String::from("validator2"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let validator3 = Validator {
         address: String::from("validator3"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let validator4 = Validator {
         address: String::from("validator4"),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
     };
 
     let validators = [
@@ -244,6 +562,8 @@ fn remove_validator() {
             validator4.clone(),
         ],
         hub_contract: hub_contract_address.to_string(),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
 
     let info = mock_info("creator", &coins(2, "token"));
@@ -256,7 +576,7 @@ fn remove_validator() {
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match _res {
         Ok(res) => {
-            let reg = REGISTRY.load(&deps.storage, validator4.address.as_str().as_bytes());
+            let reg = REGISTRY.load(&deps.storage, validator4.address.as_bytes());
             assert!(reg.is_err(), "Validator was not removed");
 
             let redelegate = &res.messages[0];
@@ -357,7 +677,7 @@ fn remove_validator() {
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match _res {
         Ok(res) => {
-            let reg = REGISTRY.load(&deps.storage, validator3.address.as_str().as_bytes());
+            let reg = REGISTRY.load(&deps.storage, validator3.address.as_bytes());
             assert!(reg.is_err(), "Validator was not removed");
 
             let redelegate = &res.messages[0];
@@ -443,7 +763,7 @@ fn remove_validator() {
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match _res {
         Ok(res) => {
-            let reg = REGISTRY.load(&deps.storage, validator2.address.as_str().as_bytes());
+            let reg = REGISTRY.load(&deps.storage, validator2.address.as_bytes());
             assert!(reg.is_err(), "Validator was not removed");
 
             let redelegate = &res.messages[0];
@@ -558,19 +878,349 @@ fn remove_validator() {
     );
 
     let msg = InstantiateMsg {
-        registry: vec![validator1.clone(), validator2],
+        registry: vec![validator1.clone(), validator2.clone()],
         hub_contract: hub_contract_address.to_string(),
+        max_commission: None,
+        rebalance_tolerance: None,
     };
 
-    let mut deps = mock_dependencies(&coins(2, "token"));
     let info = mock_info("creator", &coins(2, "token"));
     let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
     let msg = ExecuteMsg::RemoveValidator {
-        address: validator1.address,
+        address: validator1.address.clone(),
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0);
+
+    // the redelegation is queued
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingRedelegations {}).unwrap();
+    let pending: Vec<PendingRedelegation> = from_binary(&res).unwrap();
+    assert_eq!(
+        pending,
+        vec![PendingRedelegation {
+            validator: validator1.address.clone(),
+            height: mock_env().block.height,
+        }]
+    );
+
+    // still can't be redelegated, the entry stays in the queue
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ProcessPendingRedelegations {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert!(PENDING_REDELEGATIONS.has(&deps.storage, validator1.address.as_bytes()));
+
+    // the validator is draining and stays in the registry
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::ValidatorsStatus {}).unwrap();
+    let statuses: Vec<ValidatorStatusResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        statuses,
+        vec![
+            ValidatorStatusResponse {
+                address: validator1.address.clone(),
+                status: ValidatorStatus::Removing,
+                total_delegated: Uint128::from(110u128),
+            },
+            ValidatorStatusResponse {
+                address: validator2.address.clone(),
+                status: ValidatorStatus::Active,
+                total_delegated: Uint128::from(110u128),
+            },
+        ]
+    );
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetValidatorsForDelegation {},
+    )
+    .unwrap();
+    let for_delegation: Vec<ValidatorResponse> = from_binary(&res).unwrap();
+    assert_eq!(for_delegation.len(), 1);
+    assert_eq!(for_delegation[0].address, validator2.address);
+
+    // only a part of the stake can be redelegated
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            FullDelegation {
+                validator: validator1.address.clone(),
+                delegator: hub_contract_address.clone(),
+                amount: coin(110, "uatom"),
+                can_redelegate: coin(60, "uatom"),
+                accumulated_rewards: vec![],
+            },
+            sample_delegation(
+                hub_contract_address.clone(),
+                validator2.address.clone(),
+                coin(110, "uatom"),
+            ),
+        ],
+        &validators,
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ProcessPendingRedelegations {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: validator1.address.clone(),
+                redelegations: vec![(validator2.address.clone(), coin(60, "uatom"))],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert!(PENDING_REDELEGATIONS.has(&deps.storage, validator1.address.as_bytes()));
+    assert!(REGISTRY.has(&deps.storage, validator1.address.as_bytes()));
+
+    // the active redelegation is over
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(
+                hub_contract_address.clone(),
+                validator1.address.clone(),
+                coin(50, "uatom"),
+            ),
+            sample_delegation(
+                hub_contract_address.clone(),
+                validator2.address.clone(),
+                coin(170, "uatom"),
+            ),
+        ],
+        &validators,
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ProcessPendingRedelegations {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: validator1.address.clone(),
+                redelegations: vec![(validator2.address.clone(), coin(50, "uatom"))],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    // the rest of the stake is redelegated, the validator is removed
+    assert!(!PENDING_REDELEGATIONS.has(&deps.storage, validator1.address.as_bytes()));
+    assert!(!REGISTRY.has(&deps.storage, validator1.address.as_bytes()));
+}
+
+#[test]
+fn check_validators() {
+    let mut deps = mock_dependencies(&[]);
+    let hub_contract_address = Addr::unchecked("hub_contract_address");
+
+    let registry_validator = |address: &str| Validator {
+        address: String::from(address),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let cosmos_validator = |address: &str, commission: u64| CosmosValidator {
+        address: String::from(address),
+        commission: Decimal::percent(commission),
+        max_commission: Default::default(),
+        max_change_rate: Default::default(),
+    };
+
+    // validator2 is out of the active set, validator3 charges too much
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator1"),
+                coin(10, "uatom"),
+            ),
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator2"),
+                coin(20, "uatom"),
+            ),
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator3"),
+                coin(30, "uatom"),
+            ),
+        ],
+        &[
+            cosmos_validator("validator1", 5),
+            cosmos_validator("validator3", 20),
+        ],
+    );
+
+    let msg = InstantiateMsg {
+        registry: vec![
+            registry_validator("validator1"),
+            registry_validator("validator2"),
+            registry_validator("validator3"),
+        ],
+        hub_contract: hub_contract_address.to_string(),
+        max_commission: Some(Decimal::percent(10)),
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetValidatorsForDelegation {},
+    )
+    .unwrap();
+    let validators: Vec<ValidatorResponse> = from_binary(&res).unwrap();
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators[0].address, "validator1");
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::CheckValidators {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    let redelegate_msg = |src_validator: &str, amount: u128| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: String::from(src_validator),
+                redelegations: vec![(String::from("validator1"), coin(amount, "uatom"))],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    assert_eq!(res.messages[0].msg, redelegate_msg("validator2", 20));
+    assert_eq!(res.messages[1].msg, redelegate_msg("validator3", 30));
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&DispatchRewards {}).unwrap(),
+            funds: vec![],
+        })
+    );
+    let unhealthy: Vec<String> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "unhealthy_validator")
+        .map(|a| a.value.clone())
+        .collect();
+    assert_eq!(unhealthy, vec!["validator2", "validator3"]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        hub_contract: None,
+        max_commission: Some(Decimal::percent(101)),
+        rebalance_tolerance: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+    assert_eq!(
+        res.err().unwrap(),
+        StdError::generic_err("max_commission must not be greater than 1")
+    );
+}
+
+#[test]
+fn jailed_validator_is_unhealthy() {
+    let mut deps = mock_dependencies(&[]);
+    let hub_contract_address = Addr::unchecked("hub_contract_address");
+
+    let registry_validator = |address: &str| Validator {
+        address: String::from(address),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let cosmos_validator = |address: &str| CosmosValidator {
+        address: String::from(address),
+        commission: Decimal::percent(5),
+        max_commission: Default::default(),
+        max_change_rate: Default::default(),
+    };
+
+    // validator2 is jailed: the Validator query still returns it, AllValidators doesn't
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator1"),
+                coin(10, "uatom"),
+            ),
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator2"),
+                coin(20, "uatom"),
+            ),
+        ],
+        &[cosmos_validator("validator1")],
+    );
+    deps.querier
+        .update_unbonded_validators(&[cosmos_validator("validator2")]);
+
+    let msg = InstantiateMsg {
+        registry: vec![
+            registry_validator("validator1"),
+            registry_validator("validator2"),
+        ],
+        hub_contract: hub_contract_address.to_string(),
+        max_commission: Some(Decimal::percent(10)),
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetValidatorsForDelegation {},
+    )
+    .unwrap();
+    let validators: Vec<ValidatorResponse> = from_binary(&res).unwrap();
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators[0].address, "validator1");
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::CheckValidators {},
+    )
+    .unwrap();
+    let unhealthy: Vec<String> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "unhealthy_validator")
+        .map(|a| a.value.clone())
+        .collect();
+    assert_eq!(unhealthy, vec!["validator2"]);
 }
 
 #[macro_export]
@@ -579,10 +1229,160 @@ macro_rules! default_validator_with_delegations {
         ValidatorResponse {
             total_delegated: Uint128::from($total as u128),
             address: Default::default(),
+            weight: DEFAULT_WEIGHT,
+            max_delegation: None,
         }
     };
 }
 
+#[test]
+fn rebalance() {
+    let mut deps = mock_dependencies(&[]);
+    let hub_contract_address = Addr::unchecked("hub_contract_address");
+
+    let registry_validator = |address: &str| Validator {
+        address: String::from(address),
+        reward_address: None,
+        moniker: None,
+        weight: None,
+        max_delegation: None,
+        active: true,
+    };
+    let cosmos_validator = |address: &str| CosmosValidator {
+        address: String::from(address),
+        commission: Default::default(),
+        max_commission: Default::default(),
+        max_change_rate: Default::default(),
+    };
+
+    // validator3 has an active redelegation, but it still can receive stake
+    set_delegation_query(
+        &mut deps.querier,
+        &[
+            sample_delegation(
+                hub_contract_address.clone(),
+                String::from("validator1"),
+                coin(100, "uatom"),
+            ),
+            FullDelegation {
+                validator: String::from("validator3"),
+                delegator: hub_contract_address.clone(),
+                amount: coin(20, "uatom"),
+                can_redelegate: coin(0, "uatom"),
+                accumulated_rewards: vec![],
+            },
+        ],
+        &[
+            cosmos_validator("validator1"),
+            cosmos_validator("validator2"),
+            cosmos_validator("validator3"),
+        ],
+    );
+
+    let msg = InstantiateMsg {
+        registry: vec![
+            registry_validator("validator1"),
+            registry_validator("validator2"),
+            registry_validator("validator3"),
+        ],
+        hub_contract: hub_contract_address.to_string(),
+        max_commission: None,
+        rebalance_tolerance: None,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::Rebalance { max_moves: 10 },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: String::from("validator1"),
+                redelegations: vec![
+                    (String::from("validator2"), coin(40, "uatom")),
+                    (String::from("validator3"), coin(20, "uatom")),
+                ],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&DispatchRewards {}).unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // the rewards are not dispatched while they are paused on the hub, the stake still moves
+    deps.querier.pause_hub_operation(PausableOperation::Rewards);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::Rebalance { max_moves: 10 },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract_address.to_string(),
+            msg: to_binary(&RedelegateProxy {
+                src_validator: String::from("validator1"),
+                redelegations: vec![
+                    (String::from("validator2"), coin(40, "uatom")),
+                    (String::from("validator3"), coin(20, "uatom")),
+                ],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_calculate_rebalance() {
+    let validators = vec![
+        default_validator_with_delegations!(100),
+        default_validator_with_delegations!(0),
+        default_validator_with_delegations!(20),
+    ];
+    let tolerance = Decimal::percent(1);
+
+    let moves = calculate_rebalance(&validators, &[true, true, true], tolerance, 10);
+    assert_eq!(
+        moves,
+        vec![(0, 1, Uint128::from(40u128)), (0, 2, Uint128::from(20u128))]
+    );
+
+    // max_moves limits the number of redelegations
+    let moves = calculate_rebalance(&validators, &[true, true, true], tolerance, 1);
+    assert_eq!(moves, vec![(0, 1, Uint128::from(40u128))]);
+
+    // validators with an active redelegation are not used as a source
+    let moves = calculate_rebalance(&validators, &[false, true, true], tolerance, 10);
+    assert!(moves.is_empty());
+
+    // validators within tolerance are left untouched
+    let validators = vec![
+        default_validator_with_delegations!(1005),
+        default_validator_with_delegations!(995),
+    ];
+    let moves = calculate_rebalance(&validators, &[true, true], tolerance, 10);
+    assert!(moves.is_empty());
+}
+
 //TODO: implement more test cases
 #[test]
 fn test_calculate_delegations() {
@@ -598,7 +1398,7 @@ fn test_calculate_delegations() {
     ];
 
     // sort validators for the right delegations
-    validators.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+    validators.sort_by_key(|v| v.total_delegated);
 
     let buffered_balance = Uint128::from(10u128);
     let delegations = calculate_delegations(buffered_balance, validators.as_slice()).unwrap();
@@ -616,6 +1416,53 @@ fn test_calculate_delegations() {
     }
 }
 
+#[test]
+fn test_calculate_delegations_weights_and_caps() {
+    let validator = |weight: u64, max_delegation: Option<u128>| ValidatorResponse {
+        total_delegated: Uint128::zero(),
+        address: Default::default(),
+        weight,
+        max_delegation: max_delegation.map(Uint128::from),
+    };
+
+    let validators = vec![validator(1, None), validator(3, None)];
+    let delegations = calculate_delegations(Uint128::from(100u128), &validators).unwrap();
+    assert_eq!(
+        delegations,
+        vec![Uint128::from(25u128), Uint128::from(75u128)]
+    );
+
+    // the capped validator gets up to its cap, the rest goes to the other one
+    let validators = vec![validator(1, None), validator(3, Some(50))];
+    let delegations = calculate_delegations(Uint128::from(100u128), &validators).unwrap();
+    assert_eq!(
+        delegations,
+        vec![Uint128::from(50u128), Uint128::from(50u128)]
+    );
+
+    // validators with zero weight receive nothing
+    let validators = vec![validator(0, None), validator(1, None), validator(1, None)];
+    let delegations = calculate_delegations(Uint128::from(11u128), &validators).unwrap();
+    assert_eq!(
+        delegations,
+        vec![Uint128::zero(), Uint128::from(6u128), Uint128::from(5u128)]
+    );
+
+    let validators = vec![validator(1, Some(10)), validator(1, Some(10))];
+    let err = calculate_delegations(Uint128::from(30u128), &validators).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Failed to distribute delegated amount completely")
+    );
+
+    let validators = vec![validator(0, None)];
+    let err = calculate_delegations(Uint128::from(30u128), &validators).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Validators set has no weight to delegate to")
+    );
+}
+
 #[test]
 fn test_calculate_undelegations() {
     let mut validators = vec![
@@ -630,7 +1477,7 @@ fn test_calculate_undelegations() {
     ];
 
     // sort validators for the right delegations
-    validators.sort_by(|v1, v2| v2.total_delegated.cmp(&v1.total_delegated));
+    validators.sort_by_key(|v| std::cmp::Reverse(v.total_delegated));
 
     let undelegate_amount = Uint128::from(100u128);
     let undelegations = calculate_undelegations(undelegate_amount, validators.clone()).unwrap();
@@ -659,7 +1506,7 @@ fn test_calculate_undelegations() {
     ];
 
     // sort validators for the right delegations
-    validators.sort_by(|v1, v2| v2.total_delegated.cmp(&v1.total_delegated));
+    validators.sort_by_key(|v| std::cmp::Reverse(v.total_delegated));
 
     let undelegate_amount = Uint128::from(10u128);
     let undelegations = calculate_undelegations(undelegate_amount, validators.clone()).unwrap();
@@ -682,7 +1529,7 @@ fn test_calculate_undelegations() {
         default_validator_with_delegations!(10),
     ];
     // sort validators for the right delegations
-    validators.sort_by(|v1, v2| v2.total_delegated.cmp(&v1.total_delegated));
+    validators.sort_by_key(|v| std::cmp::Reverse(v.total_delegated));
 
     let undelegate_amount = Uint128::from(1000u128);
     if let Some(e) = calculate_undelegations(undelegate_amount, validators.clone()).err() {
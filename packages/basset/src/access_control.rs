@@ -0,0 +1,132 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, MessageInfo, Order, Response, StdError, StdResult, Storage,
+};
+use cw_storage_plus::Map;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Operations the owner can delegate to other addresses
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Updates the hub parameters
+    ParamsAdmin,
+    /// Adds, updates and removes validators of the registry
+    ValidatorManager,
+    /// Updates the fee rates and the lido fee recipients of the rewards dispatcher
+    FeeManager,
+    /// Pauses the contracts
+    Pauser,
+    /// Unpauses the contracts
+    Unpauser,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::ParamsAdmin,
+        Role::ValidatorManager,
+        Role::FeeManager,
+        Role::Pauser,
+        Role::Unpauser,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::ParamsAdmin => "params_admin",
+            Role::ValidatorManager => "validator_manager",
+            Role::FeeManager => "fee_manager",
+            Role::Pauser => "pauser",
+            Role::Unpauser => "unpauser",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembers {
+    pub role: Role,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<RoleMembers>,
+}
+
+// Addresses granted a role, keyed by (role, address)
+pub const ROLES: Map<(&str, &str), bool> = Map::new("roles");
+
+pub fn has_role(storage: &dyn Storage, role: Role, address: &Addr) -> bool {
+    ROLES.has(storage, (role.as_str(), address.as_str()))
+}
+
+/// Fail with "unauthorized" unless the sender is the owner or has the role
+pub fn ensure_owner_or_role(
+    storage: &dyn Storage,
+    owner: &Addr,
+    sender: &Addr,
+    role: Role,
+) -> StdResult<()> {
+    if sender != owner && !has_role(storage, role, sender) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    Ok(())
+}
+
+/// Grant the role to the address. Only the owner is allowed to execute
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: &Addr,
+    role: Role,
+    address: String,
+) -> StdResult<Response> {
+    if info.sender != *owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (role.as_str(), address.as_str()), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "grant_role"),
+        attr("role", role.as_str()),
+        attr("address", address),
+    ]))
+}
+
+/// Revoke the role from the address. Only the owner is allowed to execute
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: &Addr,
+    role: Role,
+    address: String,
+) -> StdResult<Response> {
+    if info.sender != *owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    ROLES.remove(deps.storage, (role.as_str(), address.as_str()));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_role"),
+        attr("role", role.as_str()),
+        attr("address", address),
+    ]))
+}
+
+pub fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let mut roles = vec![];
+    for role in Role::ALL.iter() {
+        let addresses = ROLES
+            .prefix(role.as_str())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(String::from_utf8)
+            .collect::<Result<Vec<String>, _>>()?;
+        roles.push(RoleMembers {
+            role: *role,
+            addresses,
+        });
+    }
+    Ok(RolesResponse { roles })
+}
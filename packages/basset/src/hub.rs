@@ -1,5 +1,6 @@
+use crate::access_control::Role;
 use cosmwasm_std::{
-    to_binary, Addr, Coin, Decimal, Deps, QueryRequest, StdResult, Uint128, WasmQuery,
+    to_binary, Addr, Binary, Coin, Decimal, Deps, QueryRequest, StdResult, Uint128, WasmQuery,
 };
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
@@ -18,6 +19,10 @@ pub struct InstantiateMsg {
     pub epoch_period: u64,
     pub underlying_coin_denom: String,
     pub unbonding_period: u64,
+    /// stAtom supply minted as dead shares to the hub by the owner's first bonds,
+    /// the other bonds are rejected until it is reached
+    #[serde(default)]
+    pub min_total_supply: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -30,6 +35,19 @@ pub struct State {
     pub prev_hub_balance: Uint128,
     pub last_unbonded_time: u64,
     pub last_processed_batch: u64,
+    /// Undelegated underlying kept on the hub balance for instant unbonding
+    #[serde(default)]
+    pub liquidity_buffer: Uint128,
+    /// Underlying kept on the hub balance to cover slashing
+    #[serde(default)]
+    pub insurance_reserve: Uint128,
+    /// Bonded rewards above the allowed exchange rate increase, kept on the hub balance
+    /// until a later BondRewards has room for them
+    #[serde(default)]
+    pub deferred_rewards: Uint128,
+    /// Underlying undelegated by the submitted batches which are not released yet
+    #[serde(default)]
+    pub unbonding_amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -38,16 +56,19 @@ pub struct Config {
     pub reward_dispatcher_contract: Option<Addr>,
     pub validators_registry_contract: Option<Addr>,
     pub statom_token_contract: Option<Addr>,
+    /// cw721 contract minting the transferable unbonding claims, the hub is its minter
+    #[serde(default)]
+    pub claims_nft_contract: Option<Addr>,
 }
 
 impl State {
     pub fn update_statom_exchange_rate(&mut self, total_issued: Uint128, requested: Uint128) {
         let actual_supply = total_issued + requested;
-        if self.total_bond_statom_amount.is_zero() || actual_supply.is_zero() {
+        let total_underlying = self.total_bond_statom_amount + self.liquidity_buffer;
+        if total_underlying.is_zero() || actual_supply.is_zero() {
             self.statom_exchange_rate = Decimal::one()
         } else {
-            self.statom_exchange_rate =
-                Decimal::from_ratio(self.total_bond_statom_amount, actual_supply);
+            self.statom_exchange_rate = Decimal::from_ratio(total_underlying, actual_supply);
         }
     }
 }
@@ -59,26 +80,80 @@ pub enum ExecuteMsg {
     /// Owner's operations
     ////////////////////
 
-    /// Set the owener
+    /// Set the contracts the hub works with
     UpdateConfig {
-        owner: Option<String>,
         rewards_dispatcher_contract: Option<String>,
         validators_registry_contract: Option<String>,
         statom_token_contract: Option<String>,
+        claims_nft_contract: Option<String>,
     },
 
-    /// update the parameters that is needed for the contract
+    /// Proposes a new owner, who has to accept the ownership within `expires_in` seconds
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+
+    /// Accepts the proposed ownership. Only the proposed owner can execute
+    AcceptOwnership {},
+
+    /// Cancels the pending ownership proposal
+    CancelOwnershipProposal {},
+
+    /// Grants the role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+
+    /// Revokes the role from the address
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+
+    /// update the parameters that is needed for the contract,
+    /// a zero max_exchange_rate_increase removes the bound
     UpdateParams {
         epoch_period: Option<u64>,
         unbonding_period: Option<u64>,
+        liquidity_buffer_target: Option<Uint128>,
+        instant_unbond_fee: Option<Decimal>,
+        max_insurance_cover: Option<Uint128>,
+        min_bond_amount: Option<Uint128>,
+        max_exchange_rate_increase: Option<Decimal>,
+        timelock_period: Option<u64>,
+        max_guardian_pause_duration: Option<u64>,
+        guardian_pause_cooldown: Option<u64>,
     },
 
-    /// Pauses the contracts. Only the owner or allowed guardians can pause the contracts
-    PauseContracts {},
+    /// Pauses the contracts. Only the owner, allowed guardians or pausers can pause the contracts.
+    /// The pause of a guardian or a pauser expires after max_guardian_pause_duration
+    PauseContracts {
+        reason: Option<String>,
+    },
+
+    /// Sets the time the paused contracts resume at, none keeps them paused until unpaused.
+    /// Only the owner can execute
+    ExtendPause {
+        expires_at: Option<u64>,
+    },
 
-    /// Unpauses the contracts. Only the owner allowed to unpause the contracts
+    /// Unpauses the contracts. Only the owner or unpausers can unpause the contracts.
+    /// The operations paused on their own stay paused
     UnpauseContracts {},
 
+    /// Pauses the given operations. Only the owner, allowed guardians or pausers can pause them.
+    /// The pause of a guardian or a pauser expires after max_guardian_pause_duration
+    PauseOperations {
+        operations: Vec<PausableOperation>,
+    },
+
+    /// Unpauses the given operations. Only the owner or unpausers can unpause them
+    UnpauseOperations {
+        operations: Vec<PausableOperation>,
+    },
+
     ////////////////////
     /// User's operations
     ////////////////////
@@ -93,12 +168,43 @@ pub enum ExecuteMsg {
     /// Dispatch Rewards
     DispatchRewards {},
 
-    /// Send back unbonded coin to the user
-    WithdrawUnbonded {},
+    /// Send back unbonded coin to the user, or to the recipient if it is set.
+    /// Unbond requests minted as claims are not included, see ReceiveNft.
+    /// Only the given batches are withdrawn if batch_ids is set, and at most limit
+    /// batches are released and at most limit unbond requests are looked at if limit is set
+    WithdrawUnbonded {
+        recipient: Option<String>,
+        batch_ids: Option<Vec<u64>>,
+        limit: Option<u32>,
+    },
+
+    /// Release the batches whose unbonding period has passed, at most limit batches if it is set
+    ProcessWithdrawRate {
+        limit: Option<u32>,
+    },
+
+    /// Undelegate the requests of the current batch once the epoch period has passed
+    SubmitBatch {},
+
+    /// Send back unbonded coin to each of the addresses, skipping the ones that opted out.
+    /// At most limit batches are released and at most limit unbond requests are looked at
+    /// per address if limit is set
+    WithdrawUnbondedFor {
+        addresses: Vec<String>,
+        limit: Option<u32>,
+    },
+
+    /// Opt out of (or back in to) withdrawals made on the sender's behalf with WithdrawUnbondedFor
+    SetWithdrawOptOut {
+        opt_out: bool,
+    },
 
     /// Check whether the slashing has happened or not
     CheckSlashing {},
 
+    /// Adds the sent underlying to the insurance reserve covering slashing
+    FundInsurance {},
+
     ////////////////////
     /// bAsset's operations
     ///////////////////
@@ -108,6 +214,12 @@ pub enum ExecuteMsg {
     /// Burn the received basset token.
     Receive(Cw20ReceiveMsg),
 
+    /// Receive interface for the unbonding claims contract.
+    /// The current owner of a claim withdraws it with SendNft to the hub carrying
+    /// Cw721HookMsg::WithdrawUnbonded, which pays out the claim and burns it.
+    /// The batch of the claim must have been released with ProcessWithdrawRate.
+    ReceiveNft(Cw721ReceiveMsg),
+
     ////////////////////
     /// internal operations
     ///////////////////
@@ -126,19 +238,202 @@ pub enum ExecuteMsg {
     RemoveGuardians {
         addresses: Vec<String>,
     },
+
+    /// Applies a timelocked params or config update once its delay has passed. Anyone can execute
+    ExecutePendingChange {
+        id: u64,
+    },
+
+    /// Drops a timelocked params or config update. Only the owner or guardians can execute
+    VetoPendingChange {
+        id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    Unbond {},
+    /// Requests unbonding of the sent stAtom. The unbonded coins are withdrawable by
+    /// the recipient if it is set, otherwise by the sender. If mint_claim is set, the request
+    /// is minted as a transferable cw721 claim to the recipient instead
+    Unbond {
+        recipient: Option<String>,
+        #[serde(default)]
+        mint_claim: bool,
+    },
+    /// Redeems stAtom immediately from the liquidity buffer at the current exchange rate
+    /// minus instant_unbond_fee
+    InstantUnbond {},
+}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721HookMsg {
+    /// Withdraws the released claim to the recipient if it is set, otherwise to the claim owner
+    WithdrawUnbonded { recipient: Option<String> },
+}
+
+/// Cw721ReceiveMsg should be de/serialized under `ReceiveNft()` variant in a ExecuteMsg,
+/// same as in cw721
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+/// Unbond request held by a cw721 claim token instead of the wait list
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondClaim {
+    pub batch_id: u64,
+    pub statom_amount: Uint128,
+}
+
+/// Messages of the cw721-base claims contract sent by the hub
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimsNftExecuteMsg {
+    Mint(ClaimMintMsg),
+    Burn { token_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimMintMsg {
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub extension: UnbondClaim,
 }
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Parameters {
     pub epoch_period: u64,
     pub underlying_coin_denom: String,
     pub unbonding_period: u64,
     pub paused: Option<bool>,
+    /// Amount of underlying the hub keeps undelegated for instant unbonding
+    #[serde(default)]
+    pub liquidity_buffer_target: Uint128,
+    /// Share of the redeemed amount kept by the hub on instant unbonding
+    #[serde(default)]
+    pub instant_unbond_fee: Decimal,
+    /// Max amount of the insurance reserve delegated back to cover a single slashing
+    #[serde(default)]
+    pub max_insurance_cover: Uint128,
+    /// Min amount of underlying accepted by BondForStAtom
+    #[serde(default)]
+    pub min_bond_amount: Uint128,
+    /// stAtom supply minted as dead shares to the hub by the owner's first bonds, set at
+    /// instantiation. The other bonds are rejected until it is reached
+    #[serde(default)]
+    pub min_total_supply: Uint128,
+    /// Max relative increase of the exchange rate by a single BondRewards,
+    /// the rewards above it are deferred to the next ones, unbounded if unset
+    #[serde(default)]
+    pub max_exchange_rate_increase: Option<Decimal>,
+    /// Delay in seconds before the owner's params and config updates can be executed,
+    /// zero applies them immediately
+    #[serde(default)]
+    pub timelock_period: u64,
+    /// Bitmap of the operations paused on their own, see `PausableOperation::mask`
+    #[serde(default)]
+    pub paused_operations: u8,
+    /// Max duration in seconds of a pause initiated by a guardian or a pauser, never zero
+    #[serde(default)]
+    pub max_guardian_pause_duration: u64,
+    /// Delay in seconds after the end of its pause before a guardian can pause the contracts again
+    #[serde(default)]
+    pub guardian_pause_cooldown: u64,
+    /// Initiator, reason and expiry of the pause of the contracts in effect
+    #[serde(default)]
+    pub pause_info: Option<PauseInfo>,
+    /// Expiry of the operations paused on their own by a guardian or a pauser,
+    /// the other paused operations stay paused until unpaused
+    #[serde(default)]
+    pub operation_pauses: Vec<OperationPause>,
+}
+
+impl Parameters {
+    /// Whether the operation is paused on its own or together with the whole contracts
+    pub fn is_paused(&self, operation: PausableOperation) -> bool {
+        self.paused.unwrap_or(false) || self.paused_operations & operation.mask() != 0
+    }
+
+    /// Unpause the contracts and the operations whose pause has expired
+    pub fn lift_expired_pause(&mut self, now: u64) {
+        let expired = matches!(
+            self.pause_info.as_ref().and_then(|info| info.expires_at),
+            Some(expires_at) if expires_at <= now
+        );
+        if expired {
+            self.paused = Some(false);
+            self.pause_info = None;
+        }
+
+        let paused_operations = &mut self.paused_operations;
+        self.operation_pauses.retain(|pause| {
+            if pause.expires_at > now {
+                return true;
+            }
+            *paused_operations &= !pause.operation.mask();
+            false
+        });
+    }
+}
+
+/// Time an operation paused on its own resumes at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperationPause {
+    pub operation: PausableOperation,
+    pub expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfo {
+    pub initiator: Addr,
+    pub reason: Option<String>,
+    pub paused_at: u64,
+    /// Time the contracts resume at, none if they stay paused until unpaused
+    pub expires_at: Option<u64>,
+}
+
+/// Operations that can be paused separately from each other
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableOperation {
+    /// BondForStAtom
+    Bond,
+    /// Unbonding through the stAtom hooks and batch submission
+    Unbond,
+    /// Withdrawal of the unbonded coins and the unbonding claims
+    Withdraw,
+    /// Rewards dispatching and bonding
+    Rewards,
+    /// stAtom transfers, sends (including the ones unbonding stAtom) and allowances
+    TokenTransfers,
+}
+
+impl PausableOperation {
+    pub fn mask(&self) -> u8 {
+        match self {
+            PausableOperation::Bond => 1,
+            PausableOperation::Unbond => 1 << 1,
+            PausableOperation::Withdraw => 1 << 2,
+            PausableOperation::Rewards => 1 << 3,
+            PausableOperation::TokenTransfers => 1 << 4,
+        }
+    }
+}
+
+/// Whether each operation is paused, on its own or together with the whole contracts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStateResponse {
+    pub paused: bool,
+    pub bond: bool,
+    pub unbond: bool,
+    pub withdraw: bool,
+    pub rewards: bool,
+    pub token_transfers: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -157,6 +452,18 @@ pub struct UnbondHistory {
     pub statom_withdraw_rate: Decimal,
 
     pub released: bool,
+    /// Part of the batch paid from the liquidity buffer instead of undelegated.
+    /// It stays on the hub balance until the batch is released
+    #[serde(default)]
+    pub buffered_amount: Uint128,
+}
+
+impl UnbondHistory {
+    /// Underlying undelegated by the batch, which comes back to the hub once it is unbonded
+    pub fn undelegated_amount(&self) -> Uint128 {
+        (self.statom_amount * self.statom_applied_exchange_rate)
+            .saturating_sub(self.buffered_amount)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -183,6 +490,10 @@ pub struct StateResponse {
     pub prev_hub_balance: Uint128,
     pub last_unbonded_time: u64,
     pub last_processed_batch: u64,
+    pub liquidity_buffer: Uint128,
+    pub insurance_reserve: Uint128,
+    pub deferred_rewards: Uint128,
+    pub unbonding_amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -191,6 +502,7 @@ pub struct ConfigResponse {
     pub reward_dispatcher_contract: Option<String>,
     pub validators_registry_contract: Option<String>,
     pub statom_token_contract: Option<String>,
+    pub claims_nft_contract: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -209,6 +521,134 @@ pub struct UnbondRequestsResponse {
     pub requests: UnbondRequest,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UnbondRequestStatus {
+    /// The request is in the current batch, which is not undelegated yet
+    Pending,
+    /// The batch is undelegated, its unbonding period has not passed yet
+    Unbonding,
+    /// The unbonding period of the batch has passed
+    Withdrawable,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondRequestDetails {
+    pub batch_id: u64,
+    pub statom_amount: Uint128,
+    pub status: UnbondRequestStatus,
+    /// Underlying amount at the applied exchange rate, or at the withdraw rate once released
+    pub expected_amount: Uint128,
+    /// Estimated time the unbonded coins become withdrawable
+    pub release_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondRequestsDetailedResponse {
+    pub address: String,
+    pub requests: Vec<UnbondRequestDetails>,
+}
+
+/// Exchange rate recorded when it is changed by bonded rewards or slashing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateSnapshot {
+    pub id: u64,
+    pub time: u64,
+    pub height: u64,
+    pub statom_exchange_rate: Decimal,
+    pub total_bond_statom_amount: Uint128,
+    pub total_statom_issued: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateHistoryResponse {
+    pub history: Vec<ExchangeRateSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AprResponse {
+    /// Annualized growth of the exchange rate over the window,
+    /// None if there are less than two snapshots in it
+    pub apr: Option<Decimal>,
+    pub from_time: Option<u64>,
+    pub to_time: Option<u64>,
+}
+
+/// Delegation shortfall of a single validator found while checking slashing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorSlashing {
+    pub validator: String,
+    /// Amount the hub expects to be delegated to the validator
+    pub expected_delegation: Uint128,
+    pub actual_delegation: Uint128,
+}
+
+/// Shortfall between the bonded amount the hub accounts for and the actual delegations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashingEvent {
+    pub id: u64,
+    pub time: u64,
+    pub height: u64,
+    pub slashed_amount: Uint128,
+    /// Part of the slashed amount delegated back from the insurance reserve
+    #[serde(default)]
+    pub covered_amount: Uint128,
+    pub validators: Vec<ValidatorSlashing>,
+    pub statom_exchange_rate_before: Decimal,
+    pub statom_exchange_rate_after: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashingEventsResponse {
+    pub events: Vec<SlashingEvent>,
+}
+
+/// UpdateParams or UpdateConfig message of the owner waiting for the timelock period
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChange {
+    pub id: u64,
+    pub msg: PendingChangeMsg,
+    pub execute_after: u64,
+}
+
+/// The messages that go through the timelock, encoded the same way as in ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingChangeMsg {
+    UpdateParams {
+        epoch_period: Option<u64>,
+        unbonding_period: Option<u64>,
+        liquidity_buffer_target: Option<Uint128>,
+        instant_unbond_fee: Option<Decimal>,
+        max_insurance_cover: Option<Uint128>,
+        min_bond_amount: Option<Uint128>,
+        max_exchange_rate_increase: Option<Decimal>,
+        timelock_period: Option<u64>,
+        max_guardian_pause_duration: Option<u64>,
+        guardian_pause_cooldown: Option<u64>,
+    },
+    UpdateConfig {
+        rewards_dispatcher_contract: Option<String>,
+        validators_registry_contract: Option<String>,
+        statom_token_contract: Option<String>,
+        claims_nft_contract: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangesResponse {
+    pub changes: Vec<PendingChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondClaimResponse {
+    pub token_id: String,
+    pub batch_id: u64,
+    pub statom_amount: Uint128,
+    pub released: bool,
+    pub withdrawable: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AllHistoryResponse {
     pub history: Vec<UnbondHistoryResponse>,
@@ -230,18 +670,45 @@ pub enum QueryMsg {
     UnbondRequests {
         address: String,
     },
+    UnbondRequestsDetailed {
+        address: String,
+    },
     AllHistory {
         start_from: Option<u64>,
         limit: Option<u32>,
     },
     Guardians,
+    UnbondClaim {
+        token_id: String,
+    },
+    ExchangeRateHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Apr {
+        window_seconds: u64,
+    },
+    SlashingEvent {
+        id: u64,
+    },
+    SlashingEvents {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    PendingOwner {},
+    Roles {},
+    PauseState {},
+    PendingChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
-pub fn is_paused(deps: Deps, hub_addr: String) -> StdResult<bool> {
+pub fn is_paused(deps: Deps, hub_addr: String, operation: PausableOperation) -> StdResult<bool> {
     let params: Parameters = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: hub_addr,
         msg: to_binary(&QueryMsg::Parameters {})?,
     }))?;
 
-    Ok(params.paused.unwrap_or(false))
+    Ok(params.is_paused(operation))
 }
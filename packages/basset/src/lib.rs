@@ -1,2 +1,4 @@
+pub mod access_control;
 pub mod contract_error;
 pub mod hub;
+pub mod ownership;
@@ -0,0 +1,112 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Owner proposed by the current owner, who becomes the owner once it accepts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    pub owner: Addr,
+    pub expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<String>,
+    pub expires_at: Option<u64>,
+}
+
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
+/// Store the proposal of a new owner, replacing the pending one.
+/// Only the current owner is allowed to execute
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: &Addr,
+    new_owner: String,
+    expires_in: u64,
+) -> StdResult<Response> {
+    if info.sender != *owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    if new_owner == *owner {
+        return Err(StdError::generic_err(
+            "the new owner must differ from the current one",
+        ));
+    }
+
+    let expires_at = env
+        .block
+        .time
+        .seconds()
+        .checked_add(expires_in)
+        .ok_or_else(|| StdError::generic_err("expires_in is too large"))?;
+    let proposal = OwnershipProposal {
+        owner: new_owner,
+        expires_at,
+    };
+    OWNERSHIP_PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("new_owner", proposal.owner),
+        attr("expires_at", proposal.expires_at.to_string()),
+    ]))
+}
+
+/// Remove the pending proposal. Only the current owner is allowed to execute
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: &Addr,
+) -> StdResult<Response> {
+    if info.sender != *owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![attr("action", "cancel_ownership_proposal")]))
+}
+
+/// Make the proposed owner the owner by calling `set_owner` with it.
+/// Only the proposed owner is allowed to execute before the proposal expires
+pub fn accept_ownership<F>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    set_owner: F,
+) -> StdResult<Response>
+where
+    F: FnOnce(DepsMut, Addr) -> StdResult<()>,
+{
+    let proposal = OWNERSHIP_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("there is no pending ownership proposal"))?;
+    if info.sender != proposal.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if env.block.time.seconds() > proposal.expires_at {
+        return Err(StdError::generic_err("the ownership proposal has expired"));
+    }
+
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+    set_owner(deps, proposal.owner.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("new_owner", proposal.owner),
+    ]))
+}
+
+pub fn query_pending_owner(deps: Deps) -> StdResult<PendingOwnerResponse> {
+    let proposal = OWNERSHIP_PROPOSAL.may_load(deps.storage)?;
+    Ok(PendingOwnerResponse {
+        pending_owner: proposal.as_ref().map(|p| p.owner.to_string()),
+        expires_at: proposal.map(|p| p.expires_at),
+    })
+}